@@ -0,0 +1,233 @@
+//! Persistent on-disk cache that survives per-session process restarts
+//!
+//! Pages are stored with their `ETag`/`Last-Modified` validators so stale
+//! entries can be revalidated with a conditional request; search results are
+//! stored with a TTL. Each entry is one JSON file named after a stable hash of
+//! its key, written atomically (temp file + rename) so concurrent server
+//! processes can share a directory.
+
+use super::CachedPage;
+use crate::models::SearchResult;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Which entries are removed first when the cache exceeds its size limit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum EvictionPolicy {
+    /// Least recently read or written
+    #[default]
+    #[serde(rename = "lru")]
+    LeastRecentlyUsed,
+    /// Oldest written, regardless of reads
+    #[serde(rename = "oldest")]
+    OldestFirst,
+}
+
+/// Location, size and freshness limits of the disk cache
+#[derive(Debug, Clone)]
+pub struct DiskCacheConfig {
+    pub dir: PathBuf,
+    /// Total size of all entries in bytes
+    pub max_bytes: u64,
+    /// Pages younger than this are used without revalidation
+    pub page_ttl: Duration,
+    /// Search results older than this are discarded
+    pub search_ttl: Duration,
+    pub eviction: EvictionPolicy,
+}
+
+impl DiskCacheConfig {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            max_bytes: 50 * 1024 * 1024,
+            page_ttl: Duration::from_secs(3600),
+            search_ttl: Duration::from_secs(3600),
+            eviction: EvictionPolicy::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SearchEntry {
+    key: String,
+    stored_at: u64,
+    results: Vec<SearchResult>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PageEntry {
+    key: String,
+    page: CachedPage,
+}
+
+/// Directory-backed cache; I/O failures are logged and treated as misses
+pub struct DiskCache {
+    config: DiskCacheConfig,
+    /// Running total of entry sizes, scanned on the first write and on each
+    /// eviction (which also picks up other processes' writes)
+    total: Mutex<Option<u64>>,
+}
+
+impl DiskCache {
+    pub fn new(config: DiskCacheConfig) -> Self {
+        Self {
+            config,
+            total: Mutex::new(None),
+        }
+    }
+
+    pub fn config(&self) -> &DiskCacheConfig {
+        &self.config
+    }
+
+    /// Stored page for `key`, fresh or not (stale pages can still be revalidated)
+    pub fn load_page(&self, key: &str) -> Option<CachedPage> {
+        let path = self.entry_path("pages", key);
+        let entry: PageEntry = self.read(&path)?;
+        (entry.key == key).then_some(entry.page)
+    }
+
+    pub fn store_page(&self, key: &str, page: &CachedPage) {
+        let entry = PageEntry {
+            key: key.to_string(),
+            page: page.clone(),
+        };
+        self.write(&self.entry_path("pages", key), &entry);
+    }
+
+    /// Whether a stored page can be used without revalidation
+    pub fn is_fresh(&self, page: &CachedPage) -> bool {
+        unix_now().saturating_sub(page.stored_at) < self.config.page_ttl.as_secs()
+    }
+
+    /// Search results for `key` if stored within the search TTL
+    pub fn load_search(&self, key: &str) -> Option<Vec<SearchResult>> {
+        let path = self.entry_path("search", key);
+        let entry: SearchEntry = self.read(&path)?;
+        let fresh = unix_now().saturating_sub(entry.stored_at) < self.config.search_ttl.as_secs();
+        (entry.key == key && fresh).then_some(entry.results)
+    }
+
+    pub fn store_search(&self, key: &str, results: &[SearchResult]) {
+        let entry = SearchEntry {
+            key: key.to_string(),
+            stored_at: unix_now(),
+            results: results.to_vec(),
+        };
+        self.write(&self.entry_path("search", key), &entry);
+    }
+
+    /// Total size of all entries in bytes
+    pub fn size(&self) -> u64 {
+        self.entries().iter().map(|(_, len, _)| len).sum()
+    }
+
+    fn entry_path(&self, kind: &str, key: &str) -> PathBuf {
+        self.config
+            .dir
+            .join(kind)
+            .join(format!("{:016x}.json", fnv1a(key.as_bytes())))
+    }
+
+    fn read<T: for<'de> Deserialize<'de>>(&self, path: &Path) -> Option<T> {
+        let data = fs::read(path).ok()?;
+        if self.config.eviction == EvictionPolicy::LeastRecentlyUsed {
+            // Reads count as use: bump the modification time
+            let _ = fs::File::options()
+                .write(true)
+                .open(path)
+                .and_then(|f| f.set_modified(SystemTime::now()));
+        }
+        serde_json::from_slice(&data).ok()
+    }
+
+    fn write<T: Serialize>(&self, path: &Path, value: &T) {
+        let replaced = fs::metadata(path).map_or(0, |meta| meta.len());
+        let result = (|| -> std::io::Result<u64> {
+            let dir = path.parent().unwrap_or(&self.config.dir);
+            fs::create_dir_all(dir)?;
+            let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+            let data = serde_json::to_vec(value)?;
+            fs::write(&tmp, &data)?;
+            fs::rename(&tmp, path)?;
+            Ok(data.len() as u64)
+        })();
+
+        match result {
+            Ok(written) => self.account(replaced, written),
+            Err(e) => eprintln!("Disk cache write to {} failed: {}", path.display(), e),
+        }
+    }
+
+    /// Update the running total after a write, evicting only once it exceeds the limit
+    fn account(&self, replaced: u64, written: u64) {
+        let mut total = self.total.lock().unwrap();
+        let size = match *total {
+            Some(size) => size.saturating_sub(replaced) + written,
+            // The first scan already includes the entry just written
+            None => self.size(),
+        };
+        *total = Some(if size > self.config.max_bytes {
+            self.evict()
+        } else {
+            size
+        });
+    }
+
+    /// Remove entries (by modification time) until the cache fits its size
+    /// limit, returning the size left
+    fn evict(&self) -> u64 {
+        let mut entries = self.entries();
+        let mut total: u64 = entries.iter().map(|(_, len, _)| len).sum();
+        if total <= self.config.max_bytes {
+            return total;
+        }
+
+        entries.sort_by_key(|(_, _, modified)| *modified);
+        for (path, len, _) in entries {
+            if total <= self.config.max_bytes {
+                break;
+            }
+            if fs::remove_file(&path).is_ok() {
+                total = total.saturating_sub(len);
+            }
+        }
+        total
+    }
+
+    fn entries(&self) -> Vec<(PathBuf, u64, SystemTime)> {
+        ["pages", "search"]
+            .iter()
+            .filter_map(|kind| fs::read_dir(self.config.dir.join(kind)).ok())
+            .flatten()
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let path = entry.path();
+                if path.extension()? != "json" {
+                    return None;
+                }
+                let meta = entry.metadata().ok()?;
+                Some((path, meta.len(), meta.modified().ok()?))
+            })
+            .collect()
+    }
+}
+
+/// Seconds since the Unix epoch
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_secs()
+}
+
+/// FNV-1a: a file name hash that stays stable across builds and processes
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ *b as u64).wrapping_mul(0x100000001b3)
+    })
+}
@@ -0,0 +1,144 @@
+//! TTL-bounded LRU cache for search and fetch results, with optional disk persistence
+
+mod disk;
+mod singleflight;
+
+pub use disk::{DiskCache, DiskCacheConfig, EvictionPolicy};
+pub use singleflight::SingleFlight;
+pub(crate) use disk::unix_now;
+
+use crate::models::{PageMetadata, Redirect};
+use serde::{Deserialize, Serialize};
+use std::hash::Hash;
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Size and freshness limits for one cache
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    /// Maximum number of entries; 0 disables the cache
+    pub max_entries: usize,
+    /// How long an entry stays valid after insertion
+    pub ttl: Duration,
+}
+
+impl CacheConfig {
+    pub fn new(max_entries: usize, ttl: Duration) -> Self {
+        Self { max_entries, ttl }
+    }
+
+    pub fn disabled() -> Self {
+        Self::new(0, Duration::ZERO)
+    }
+}
+
+/// Page body with the validators needed to revalidate it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedPage {
+    pub body: String,
+    pub content_type: Option<String>,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    /// Seconds since the Unix epoch when the body was last confirmed current
+    pub stored_at: u64,
+    /// Metadata read while converting a non-HTML document (e.g. a PDF) to text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<PageMetadata>,
+    /// Redirects followed to reach the body, so cached copies report them too
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redirects: Vec<Redirect>,
+}
+
+impl CachedPage {
+    /// URL the body came from, when redirects led away from the requested one
+    pub fn final_url(&self) -> Option<&str> {
+        self.redirects.last().map(|r| r.url.as_str())
+    }
+}
+
+/// Hit/miss counters and current size
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+}
+
+/// Thread-safe LRU cache whose entries expire after a fixed TTL
+pub struct TtlCache<K: Hash + Eq, V: Clone> {
+    entries: Option<Mutex<lru::LruCache<K, (Instant, V)>>>,
+    ttl: Duration,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<K: Hash + Eq, V: Clone> TtlCache<K, V> {
+    pub fn new(config: &CacheConfig) -> Self {
+        Self {
+            entries: NonZeroUsize::new(config.max_entries)
+                .map(|cap| Mutex::new(lru::LruCache::new(cap))),
+            ttl: config.ttl,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Fresh value for `key`, marking it most recently used
+    pub fn get(&self, key: &K) -> Option<V> {
+        let entries = self.entries.as_ref()?;
+        let mut entries = entries.lock().unwrap();
+
+        let value = match entries.get(key) {
+            Some((inserted, value)) if inserted.elapsed() < self.ttl => Some(value.clone()),
+            Some(_) => {
+                entries.pop(key);
+                None
+            }
+            None => None,
+        };
+
+        let counter = if value.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        value
+    }
+
+    /// Insert or replace a value, evicting the least recently used entry when full
+    pub fn insert(&self, key: K, value: V) {
+        if let Some(entries) = &self.entries {
+            entries.lock().unwrap().put(key, (Instant::now(), value));
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self
+                .entries
+                .as_ref()
+                .map(|e| e.lock().unwrap().len())
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// Canonical cache key for a URL: lowercase scheme and host, default port
+/// and fragment removed. Unparseable input is used verbatim.
+pub fn canonical_url(url: &str) -> String {
+    match reqwest::Url::parse(url.trim()) {
+        Ok(mut parsed) => {
+            parsed.set_fragment(None);
+            if parsed.query() == Some("") {
+                parsed.set_query(None);
+            }
+            parsed.to_string()
+        }
+        Err(_) => url.trim().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,121 @@
+//! Request coalescing: concurrent identical calls share one execution
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use tokio::sync::watch;
+
+type InFlight<K, V, E> = Arc<Mutex<HashMap<K, watch::Receiver<Option<Result<V, E>>>>>>;
+
+/// Deduplicates in-flight work by key.
+///
+/// The first caller for a key (the leader) runs its future; callers arriving
+/// while it is in flight wait for and receive a clone of the leader's result,
+/// error included, so every caller sees the same typed error.
+/// If the leader is cancelled, one of the waiters takes over.
+pub struct SingleFlight<K, V, E> {
+    inflight: InFlight<K, V, E>,
+}
+
+enum Role<V, E> {
+    Leader(watch::Sender<Option<Result<V, E>>>),
+    Follower(watch::Receiver<Option<Result<V, E>>>),
+}
+
+/// Removes the leader's entry even if its future is dropped mid-flight
+struct LeaderGuard<K: Hash + Eq, V, E> {
+    inflight: InFlight<K, V, E>,
+    key: Option<K>,
+}
+
+impl<K: Hash + Eq, V, E> Drop for LeaderGuard<K, V, E> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.inflight.lock().unwrap().remove(&key);
+        }
+    }
+}
+
+impl<K, V, E> SingleFlight<K, V, E>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    E: Clone,
+{
+    pub fn new() -> Self {
+        Self {
+            inflight: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Run `work` unless an identical call is already in flight, in which
+    /// case wait for that call's result instead
+    pub async fn run<F, Fut>(&self, key: K, work: F) -> Result<V, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V, E>>,
+    {
+        loop {
+            let role = {
+                let mut inflight = self.inflight.lock().unwrap();
+                match inflight.get(&key) {
+                    Some(rx) => Role::Follower(rx.clone()),
+                    None => {
+                        let (tx, rx) = watch::channel(None);
+                        inflight.insert(key.clone(), rx);
+                        Role::Leader(tx)
+                    }
+                }
+            };
+
+            match role {
+                Role::Leader(tx) => return self.lead(key, tx, work).await,
+                Role::Follower(mut rx) => {
+                    // An error from `wait_for` means the leader was dropped: retry
+                    if let Ok(result) = rx.wait_for(Option::is_some).await {
+                        return result.clone().expect("wait_for only returns a completed result");
+                    }
+                }
+            }
+        }
+    }
+
+    /// Number of distinct calls currently in flight
+    pub fn in_flight(&self) -> usize {
+        self.inflight.lock().unwrap().len()
+    }
+
+    async fn lead<F, Fut>(
+        &self,
+        key: K,
+        tx: watch::Sender<Option<Result<V, E>>>,
+        work: F,
+    ) -> Result<V, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V, E>>,
+    {
+        let guard = LeaderGuard {
+            inflight: self.inflight.clone(),
+            key: Some(key),
+        };
+
+        let result = work().await;
+        // Unregister before publishing so late arrivals start a fresh call
+        drop(guard);
+        let _ = tx.send(Some(result.clone()));
+        result
+    }
+}
+
+impl<K, V, E> Default for SingleFlight<K, V, E>
+where
+    K: Hash + Eq + Clone,
+    V: Clone,
+    E: Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
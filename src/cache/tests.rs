@@ -0,0 +1,291 @@
+//! Unit tests for result caching
+
+use super::*;
+
+fn cache(max_entries: usize, ttl_secs: u64) -> TtlCache<String, String> {
+    TtlCache::new(&CacheConfig::new(max_entries, Duration::from_secs(ttl_secs)))
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_get_and_insert() {
+    let cache = cache(10, 60);
+
+    assert_eq!(cache.get(&"a".to_string()), None);
+    cache.insert("a".to_string(), "1".to_string());
+    assert_eq!(cache.get(&"a".to_string()), Some("1".to_string()));
+
+    assert_eq!(
+        cache.stats(),
+        CacheStats {
+            hits: 1,
+            misses: 1,
+            entries: 1
+        }
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_evicts_least_recently_used() {
+    let cache = cache(2, 60);
+    cache.insert("a".to_string(), "1".to_string());
+    cache.insert("b".to_string(), "2".to_string());
+
+    // Touch "a" so "b" becomes the eviction candidate
+    cache.get(&"a".to_string());
+    cache.insert("c".to_string(), "3".to_string());
+
+    assert!(cache.get(&"a".to_string()).is_some());
+    assert!(cache.get(&"b".to_string()).is_none());
+    assert!(cache.get(&"c".to_string()).is_some());
+    assert_eq!(cache.stats().entries, 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_entries_expire_after_ttl() {
+    let cache = cache(10, 60);
+    cache.insert("a".to_string(), "1".to_string());
+
+    tokio::time::advance(Duration::from_secs(59)).await;
+    assert!(cache.get(&"a".to_string()).is_some());
+
+    tokio::time::advance(Duration::from_secs(1)).await;
+    assert!(cache.get(&"a".to_string()).is_none());
+    assert_eq!(cache.stats().entries, 0);
+}
+
+#[test]
+fn test_disabled_cache_stores_nothing() {
+    let cache: TtlCache<String, String> = TtlCache::new(&CacheConfig::disabled());
+    cache.insert("a".to_string(), "1".to_string());
+
+    assert!(cache.get(&"a".to_string()).is_none());
+    assert_eq!(cache.stats().entries, 0);
+}
+
+#[test]
+fn test_canonical_url() {
+    assert_eq!(
+        canonical_url("HTTPS://Example.COM:443/Docs?x=1#section"),
+        "https://example.com/Docs?x=1"
+    );
+    assert_eq!(canonical_url("https://example.com"), "https://example.com/");
+    assert_eq!(canonical_url("https://example.com/a?"), "https://example.com/a");
+    assert_eq!(canonical_url(" not a url "), "not a url");
+}
+
+fn page(body: &str) -> CachedPage {
+    CachedPage {
+        body: body.to_string(),
+        content_type: Some("text/html".to_string()),
+        etag: Some("\"v1\"".to_string()),
+        last_modified: None,
+        stored_at: unix_now(),
+        metadata: None,
+        redirects: Vec::new(),
+    }
+}
+
+fn result(title: &str) -> crate::models::SearchResult {
+    crate::models::SearchResult {
+        title: title.to_string(),
+        url: format!("https://example.com/{}", title),
+        snippet: String::new(),
+    }
+}
+
+#[test]
+fn test_disk_page_roundtrip_across_instances() {
+    let dir = tempfile::tempdir().unwrap();
+    DiskCache::new(DiskCacheConfig::new(dir.path())).store_page("https://a.example/", &page("<p>a</p>"));
+
+    // A new instance (e.g. the next session's process) sees the same entry
+    let disk = DiskCache::new(DiskCacheConfig::new(dir.path()));
+    let loaded = disk.load_page("https://a.example/").unwrap();
+
+    assert_eq!(loaded.body, "<p>a</p>");
+    assert_eq!(loaded.etag.as_deref(), Some("\"v1\""));
+    assert!(disk.is_fresh(&loaded));
+    assert!(disk.load_page("https://b.example/").is_none());
+}
+
+#[test]
+fn test_disk_page_staleness() {
+    let dir = tempfile::tempdir().unwrap();
+    let disk = DiskCache::new(DiskCacheConfig {
+        page_ttl: Duration::from_secs(60),
+        ..DiskCacheConfig::new(dir.path())
+    });
+    let mut old = page("<p>old</p>");
+    old.stored_at -= 120;
+    disk.store_page("k", &old);
+
+    // Stale pages are still returned so they can be revalidated
+    let loaded = disk.load_page("k").unwrap();
+    assert!(!disk.is_fresh(&loaded));
+}
+
+#[test]
+fn test_disk_search_ttl() {
+    let dir = tempfile::tempdir().unwrap();
+    let fresh = DiskCache::new(DiskCacheConfig::new(dir.path()));
+    fresh.store_search("rust", &[result("a"), result("b")]);
+    assert_eq!(fresh.load_search("rust").unwrap().len(), 2);
+
+    let expired = DiskCache::new(DiskCacheConfig {
+        search_ttl: Duration::ZERO,
+        ..DiskCacheConfig::new(dir.path())
+    });
+    assert!(expired.load_search("rust").is_none());
+}
+
+fn small_cache(dir: &tempfile::TempDir, eviction: EvictionPolicy) -> DiskCache {
+    let entry_size = {
+        let probe = tempfile::tempdir().unwrap();
+        let disk = DiskCache::new(DiskCacheConfig::new(probe.path()));
+        disk.store_page("k0", &page(&"x".repeat(1000)));
+        disk.size()
+    };
+    DiskCache::new(DiskCacheConfig {
+        max_bytes: entry_size * 2,
+        eviction,
+        ..DiskCacheConfig::new(dir.path())
+    })
+}
+
+#[test]
+fn test_disk_eviction_lru() {
+    let dir = tempfile::tempdir().unwrap();
+    let disk = small_cache(&dir, EvictionPolicy::LeastRecentlyUsed);
+
+    disk.store_page("k1", &page(&"x".repeat(1000)));
+    std::thread::sleep(Duration::from_millis(20));
+    disk.store_page("k2", &page(&"x".repeat(1000)));
+    std::thread::sleep(Duration::from_millis(20));
+    // Reading k1 makes k2 the least recently used
+    disk.load_page("k1").unwrap();
+    std::thread::sleep(Duration::from_millis(20));
+    disk.store_page("k3", &page(&"x".repeat(1000)));
+
+    assert!(disk.load_page("k1").is_some());
+    assert!(disk.load_page("k2").is_none());
+    assert!(disk.load_page("k3").is_some());
+    assert!(disk.size() <= disk.config().max_bytes);
+}
+
+#[test]
+fn test_disk_eviction_oldest_first() {
+    let dir = tempfile::tempdir().unwrap();
+    let disk = small_cache(&dir, EvictionPolicy::OldestFirst);
+
+    disk.store_page("k1", &page(&"x".repeat(1000)));
+    std::thread::sleep(Duration::from_millis(20));
+    disk.store_page("k2", &page(&"x".repeat(1000)));
+    std::thread::sleep(Duration::from_millis(20));
+    // Reads don't protect k1 under this policy
+    disk.load_page("k1").unwrap();
+    disk.store_page("k3", &page(&"x".repeat(1000)));
+
+    assert!(disk.load_page("k1").is_none());
+    assert!(disk.load_page("k2").is_some());
+    assert!(disk.load_page("k3").is_some());
+}
+
+#[test]
+fn test_disk_rewrites_do_not_trigger_eviction() {
+    let dir = tempfile::tempdir().unwrap();
+    let disk = small_cache(&dir, EvictionPolicy::OldestFirst);
+
+    disk.store_page("k1", &page(&"x".repeat(1000)));
+    std::thread::sleep(Duration::from_millis(20));
+    // Rewriting an entry in place replaces its size rather than adding to it
+    for _ in 0..5 {
+        disk.store_page("k2", &page(&"x".repeat(1000)));
+    }
+
+    assert!(disk.load_page("k1").is_some());
+    assert!(disk.load_page("k2").is_some());
+    assert!(disk.size() <= disk.config().max_bytes);
+}
+
+#[tokio::test]
+async fn test_single_flight_coalesces_concurrent_calls() {
+    let flight: SingleFlight<String, u32, String> = SingleFlight::new();
+    let calls = std::sync::atomic::AtomicU32::new(0);
+    let work = || async {
+        calls.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        Ok(42)
+    };
+
+    let (a, b, c) = tokio::join!(
+        flight.run("k".to_string(), work),
+        flight.run("k".to_string(), work),
+        flight.run("k".to_string(), work),
+    );
+
+    assert_eq!((a.unwrap(), b.unwrap(), c.unwrap()), (42, 42, 42));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(flight.in_flight(), 0);
+}
+
+#[tokio::test]
+async fn test_single_flight_distinct_keys_run_separately() {
+    let flight: SingleFlight<String, String, String> = SingleFlight::new();
+
+    let (a, b) = tokio::join!(
+        flight.run("a".to_string(), || async { Ok("A".to_string()) }),
+        flight.run("b".to_string(), || async { Ok("B".to_string()) }),
+    );
+
+    assert_eq!(a.unwrap(), "A");
+    assert_eq!(b.unwrap(), "B");
+}
+
+#[tokio::test]
+async fn test_single_flight_shares_errors() {
+    let flight: SingleFlight<String, u32, std::sync::Arc<std::io::Error>> = SingleFlight::new();
+    let work = || async {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        Err(std::sync::Arc::new(std::io::Error::new(std::io::ErrorKind::TimedOut, "upstream failed")))
+    };
+
+    let (a, b) = tokio::join!(
+        flight.run("k".to_string(), work),
+        flight.run("k".to_string(), work),
+    );
+
+    // Both callers get the typed error, not just its message
+    for err in [a.unwrap_err(), b.unwrap_err()] {
+        assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+        assert_eq!(err.to_string(), "upstream failed");
+    }
+}
+
+#[tokio::test]
+async fn test_single_flight_follower_takes_over_cancelled_leader() {
+    let flight = std::sync::Arc::new(SingleFlight::<String, u32, String>::new());
+
+    let leader = tokio::spawn({
+        let flight = flight.clone();
+        async move {
+            flight
+                .run("k".to_string(), || async {
+                    tokio::time::sleep(Duration::from_secs(60)).await;
+                    Ok(1)
+                })
+                .await
+        }
+    });
+    tokio::task::yield_now().await;
+    assert_eq!(flight.in_flight(), 1);
+
+    let follower = tokio::spawn({
+        let flight = flight.clone();
+        async move { flight.run("k".to_string(), || async { Ok(2) }).await }
+    });
+    tokio::task::yield_now().await;
+    leader.abort();
+
+    assert_eq!(follower.await.unwrap().unwrap(), 2);
+    assert_eq!(flight.in_flight(), 0);
+}
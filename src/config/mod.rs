@@ -0,0 +1,426 @@
+//! Server configuration from a TOML file and `MCP_WEBSEARCH_*` environment variables
+
+use crate::cache::{DiskCacheConfig, EvictionPolicy};
+use crate::domains::DomainPolicy;
+use crate::retry::RetryPolicy;
+use crate::search::ProxyConfig;
+use crate::ssrf::SsrfPolicy;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use toml::{Table, Value};
+
+/// Environment variable naming the config file when no path is given
+pub const CONFIG_PATH_VAR: &str = "MCP_WEBSEARCH_CONFIG";
+
+/// Every setting, with defaults matching a server started without configuration
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub http: HttpSettings,
+    pub search: SearchSettings,
+    pub fetch: FetchSettings,
+    pub cache: CacheSettings,
+    pub domains: DomainSettings,
+    pub robots: RobotsSettings,
+    pub proxy: ProxySettings,
+}
+
+/// Settings shared by every outgoing request
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HttpSettings {
+    pub user_agent: String,
+    pub timeout_secs: u64,
+}
+
+impl Default for HttpSettings {
+    fn default() -> Self {
+        Self {
+            user_agent: crate::search::DEFAULT_USER_AGENT.to_string(),
+            timeout_secs: 30,
+        }
+    }
+}
+
+/// DuckDuckGo searches
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SearchSettings {
+    pub requests_per_minute: usize,
+    pub min_delay_ms: u64,
+    /// Total attempts per search, including the first
+    pub retry_attempts: u32,
+    /// Delay before the first retry, doubled for every further retry
+    pub retry_base_delay_ms: u64,
+    /// Fraction of each retry delay that is randomized (0.0 to 1.0)
+    pub retry_jitter: f64,
+    /// HTTP statuses worth retrying
+    pub retry_statuses: Vec<u16>,
+    pub max_body_bytes: u64,
+    /// Ledger file sharing the search budget between local server processes
+    pub rate_ledger: Option<PathBuf>,
+}
+
+impl Default for SearchSettings {
+    fn default() -> Self {
+        let retry = RetryPolicy::default();
+        Self {
+            requests_per_minute: 20,
+            min_delay_ms: 3000,
+            retry_attempts: 3,
+            retry_base_delay_ms: retry.base_delay.as_millis() as u64,
+            retry_jitter: retry.jitter,
+            retry_statuses: retry.retryable_statuses,
+            max_body_bytes: crate::search::DEFAULT_MAX_SEARCH_BODY,
+            rate_ledger: None,
+        }
+    }
+}
+
+/// Page fetches (`fetch_content`, `extract_links`, `fetch_selector`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FetchSettings {
+    /// Budget for each fetched host
+    pub requests_per_minute: usize,
+    pub burst: usize,
+    pub min_delay_ms: u64,
+    pub retry_attempts: u32,
+    pub retry_base_delay_ms: u64,
+    pub retry_jitter: f64,
+    pub retry_statuses: Vec<u16>,
+    pub max_body_bytes: u64,
+    /// Content returned when a call gives no `max_length`
+    pub default_max_length: usize,
+    pub max_redirects: usize,
+    pub allow_https_downgrade: bool,
+    /// Restricted hosts, IPs and CIDR ranges fetches may reach anyway
+    pub allowlist: Vec<String>,
+}
+
+impl Default for FetchSettings {
+    fn default() -> Self {
+        let retry = RetryPolicy::default();
+        Self {
+            requests_per_minute: 20,
+            burst: 5,
+            min_delay_ms: 1000,
+            retry_attempts: 3,
+            retry_base_delay_ms: retry.base_delay.as_millis() as u64,
+            retry_jitter: retry.jitter,
+            retry_statuses: retry.retryable_statuses,
+            max_body_bytes: crate::search::DEFAULT_MAX_FETCH_BODY,
+            default_max_length: crate::models::FetchOptions::DEFAULT_MAX_LENGTH,
+            max_redirects: 10,
+            allow_https_downgrade: false,
+            allowlist: Vec::new(),
+        }
+    }
+}
+
+/// In-memory caches and the optional on-disk cache
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CacheSettings {
+    pub search_entries: usize,
+    pub search_ttl_secs: u64,
+    pub fetch_entries: usize,
+    pub fetch_ttl_secs: u64,
+    /// Directory for the persistent cache (disabled when unset)
+    pub dir: Option<PathBuf>,
+    /// Total size of the persistent cache
+    pub disk_max_bytes: u64,
+    /// `lru` (least recently used) or `oldest` (oldest written) entries go first
+    pub disk_eviction: EvictionPolicy,
+    /// Stored pages younger than this are used without revalidation
+    pub disk_page_ttl_secs: u64,
+    pub disk_search_ttl_secs: u64,
+}
+
+impl Default for CacheSettings {
+    fn default() -> Self {
+        let disk = DiskCacheConfig::new("");
+        Self {
+            search_entries: 200,
+            search_ttl_secs: 600,
+            fetch_entries: 100,
+            fetch_ttl_secs: 900,
+            dir: None,
+            disk_max_bytes: disk.max_bytes,
+            disk_eviction: disk.eviction,
+            disk_page_ttl_secs: disk.page_ttl.as_secs(),
+            disk_search_ttl_secs: disk.search_ttl.as_secs(),
+        }
+    }
+}
+
+/// Domain allow/deny patterns for search results and fetches
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DomainSettings {
+    pub allowed: Vec<String>,
+    pub blocked: Vec<String>,
+}
+
+/// robots.txt compliance for page fetches
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RobotsSettings {
+    pub enabled: bool,
+    pub user_agent: String,
+    pub cache_ttl_secs: u64,
+    pub max_crawl_wait_secs: u64,
+}
+
+impl Default for RobotsSettings {
+    fn default() -> Self {
+        let defaults = crate::search::RobotsConfig::default();
+        Self {
+            enabled: false,
+            user_agent: defaults.user_agent,
+            cache_ttl_secs: defaults.cache_ttl.as_secs(),
+            max_crawl_wait_secs: defaults.max_crawl_wait.as_secs(),
+        }
+    }
+}
+
+/// Upstream proxies; `search` and `fetch` override `url` for that traffic
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProxySettings {
+    /// Proxy for all traffic: `http://`, `https://`, `socks5://` or `socks5h://`, with optional `user:pass@`
+    pub url: Option<String>,
+    /// Proxy for DuckDuckGo searches
+    pub search: Option<String>,
+    /// Proxy for page fetches
+    pub fetch: Option<String>,
+    /// Hosts, `.domains`, IPs or CIDR ranges reached without the proxy
+    pub no_proxy: Vec<String>,
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Text,
+    Integer,
+    Float,
+    Bool,
+    List,
+    IntegerList,
+}
+
+/// Environment variables and the settings they override
+const ENV_OVERRIDES: &[(&str, &str, Kind)] = &[
+    ("MCP_WEBSEARCH_USER_AGENT", "http.user_agent", Kind::Text),
+    ("MCP_WEBSEARCH_TIMEOUT_SECS", "http.timeout_secs", Kind::Integer),
+    ("MCP_WEBSEARCH_SEARCH_RATE_LIMIT", "search.requests_per_minute", Kind::Integer),
+    ("MCP_WEBSEARCH_SEARCH_MIN_DELAY_MS", "search.min_delay_ms", Kind::Integer),
+    ("MCP_WEBSEARCH_SEARCH_RETRIES", "search.retry_attempts", Kind::Integer),
+    ("MCP_WEBSEARCH_SEARCH_RETRY_DELAY_MS", "search.retry_base_delay_ms", Kind::Integer),
+    ("MCP_WEBSEARCH_SEARCH_RETRY_JITTER", "search.retry_jitter", Kind::Float),
+    ("MCP_WEBSEARCH_SEARCH_RETRY_STATUSES", "search.retry_statuses", Kind::IntegerList),
+    ("MCP_WEBSEARCH_SEARCH_MAX_BODY", "search.max_body_bytes", Kind::Integer),
+    ("MCP_WEBSEARCH_RATE_LEDGER", "search.rate_ledger", Kind::Text),
+    ("MCP_WEBSEARCH_FETCH_RATE_LIMIT", "fetch.requests_per_minute", Kind::Integer),
+    ("MCP_WEBSEARCH_FETCH_BURST", "fetch.burst", Kind::Integer),
+    ("MCP_WEBSEARCH_FETCH_MIN_DELAY_MS", "fetch.min_delay_ms", Kind::Integer),
+    ("MCP_WEBSEARCH_FETCH_RETRIES", "fetch.retry_attempts", Kind::Integer),
+    ("MCP_WEBSEARCH_FETCH_RETRY_DELAY_MS", "fetch.retry_base_delay_ms", Kind::Integer),
+    ("MCP_WEBSEARCH_FETCH_RETRY_JITTER", "fetch.retry_jitter", Kind::Float),
+    ("MCP_WEBSEARCH_FETCH_RETRY_STATUSES", "fetch.retry_statuses", Kind::IntegerList),
+    ("MCP_WEBSEARCH_FETCH_MAX_BODY", "fetch.max_body_bytes", Kind::Integer),
+    ("MCP_WEBSEARCH_MAX_LENGTH", "fetch.default_max_length", Kind::Integer),
+    ("MCP_WEBSEARCH_MAX_REDIRECTS", "fetch.max_redirects", Kind::Integer),
+    ("MCP_WEBSEARCH_FETCH_ALLOWLIST", "fetch.allowlist", Kind::List),
+    ("MCP_WEBSEARCH_ALLOW_HTTPS_DOWNGRADE", "fetch.allow_https_downgrade", Kind::Bool),
+    ("MCP_WEBSEARCH_CACHE_DIR", "cache.dir", Kind::Text),
+    ("MCP_WEBSEARCH_CACHE_MAX_BYTES", "cache.disk_max_bytes", Kind::Integer),
+    ("MCP_WEBSEARCH_CACHE_EVICTION", "cache.disk_eviction", Kind::Text),
+    ("MCP_WEBSEARCH_CACHE_PAGE_TTL_SECS", "cache.disk_page_ttl_secs", Kind::Integer),
+    ("MCP_WEBSEARCH_CACHE_SEARCH_TTL_SECS", "cache.disk_search_ttl_secs", Kind::Integer),
+    ("MCP_WEBSEARCH_ALLOWED_DOMAINS", "domains.allowed", Kind::List),
+    ("MCP_WEBSEARCH_BLOCKED_DOMAINS", "domains.blocked", Kind::List),
+    ("MCP_WEBSEARCH_RESPECT_ROBOTS", "robots.enabled", Kind::Bool),
+    ("MCP_WEBSEARCH_ROBOTS_USER_AGENT", "robots.user_agent", Kind::Text),
+    ("MCP_WEBSEARCH_ROBOTS_CACHE_TTL_SECS", "robots.cache_ttl_secs", Kind::Integer),
+    ("MCP_WEBSEARCH_ROBOTS_MAX_CRAWL_WAIT_SECS", "robots.max_crawl_wait_secs", Kind::Integer),
+    ("MCP_WEBSEARCH_PROXY", "proxy.url", Kind::Text),
+    ("MCP_WEBSEARCH_SEARCH_PROXY", "proxy.search", Kind::Text),
+    ("MCP_WEBSEARCH_FETCH_PROXY", "proxy.fetch", Kind::Text),
+    ("MCP_WEBSEARCH_NO_PROXY", "proxy.no_proxy", Kind::List),
+];
+
+impl Config {
+    /// Read the config file (`path`, or `MCP_WEBSEARCH_CONFIG` when `None`),
+    /// apply environment overrides and validate the result
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let path = path
+            .map(Path::to_path_buf)
+            .or_else(|| std::env::var_os(CONFIG_PATH_VAR).map(PathBuf::from));
+        let file = match &path {
+            Some(path) => Some(
+                std::fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read config file {}", path.display()))?,
+            ),
+            None => None,
+        };
+
+        Self::from_sources(file.as_deref(), |name| std::env::var(name).ok()).with_context(|| match &path {
+            Some(path) => format!("Invalid configuration in {}", path.display()),
+            None => "Invalid configuration".to_string(),
+        })
+    }
+
+    /// Build a config from TOML text and an environment lookup; the environment wins
+    pub fn from_sources(file: Option<&str>, env: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let mut table: Table = match file {
+            Some(text) => text.parse()?,
+            None => Table::new(),
+        };
+
+        for (name, key, kind) in ENV_OVERRIDES {
+            let Some(raw) = env(name) else {
+                continue;
+            };
+            let value = env_value(&raw, *kind)
+                .with_context(|| format!("Invalid value '{}' for {}", raw, name))?;
+            let (section, field) = key.split_once('.').unwrap();
+            let section = table
+                .entry(section)
+                .or_insert_with(|| Value::Table(Table::new()))
+                .as_table_mut()
+                .with_context(|| format!("'{}' must be a table", section))?;
+            section.insert(field.to_string(), value);
+        }
+
+        let config: Self = Value::Table(table).try_into()?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Reject values the server cannot run with
+    pub fn validate(&self) -> Result<()> {
+        let at_least_one = [
+            ("http.timeout_secs", self.http.timeout_secs as usize),
+            ("search.requests_per_minute", self.search.requests_per_minute),
+            ("search.retry_attempts", self.search.retry_attempts as usize),
+            ("search.max_body_bytes", self.search.max_body_bytes as usize),
+            ("fetch.requests_per_minute", self.fetch.requests_per_minute),
+            ("fetch.burst", self.fetch.burst),
+            ("fetch.retry_attempts", self.fetch.retry_attempts as usize),
+            ("fetch.max_body_bytes", self.fetch.max_body_bytes as usize),
+            ("fetch.default_max_length", self.fetch.default_max_length),
+            ("cache.disk_max_bytes", self.cache.disk_max_bytes as usize),
+        ];
+        for (key, value) in at_least_one {
+            if value == 0 {
+                anyhow::bail!("{} must be at least 1", key);
+            }
+        }
+
+        for (section, retry) in [("search", &self.search_retry()), ("fetch", &self.fetch_retry())] {
+            if !(0.0..=1.0).contains(&retry.jitter) {
+                anyhow::bail!("{}.retry_jitter must be between 0.0 and 1.0", section);
+            }
+            if let Some(status) = retry.retryable_statuses.iter().find(|s| !(100..=599).contains(*s)) {
+                anyhow::bail!("{}.retry_statuses: {} is not an HTTP status", section, status);
+            }
+        }
+
+        if reqwest::header::HeaderValue::from_str(&self.http.user_agent).is_err() || self.http.user_agent.is_empty() {
+            anyhow::bail!("http.user_agent must be a non-empty header value");
+        }
+        if self.robots.user_agent.trim().is_empty() {
+            anyhow::bail!("robots.user_agent must not be empty");
+        }
+
+        self.ssrf_policy()?;
+        self.domain_policy()?;
+        self.proxies()?;
+        Ok(())
+    }
+
+    /// Proxies for searches and for page fetches
+    pub fn proxies(&self) -> Result<(Option<ProxyConfig>, Option<ProxyConfig>)> {
+        let proxy = &self.proxy;
+        let build = |url: Option<&String>| -> Result<Option<ProxyConfig>> {
+            url.filter(|url| !url.trim().is_empty())
+                .map(|url| Ok(ProxyConfig::new(url)?.with_no_proxy(&proxy.no_proxy)))
+                .transpose()
+        };
+        Ok((
+            build(proxy.search.as_ref().or(proxy.url.as_ref()))?,
+            build(proxy.fetch.as_ref().or(proxy.url.as_ref()))?,
+        ))
+    }
+
+    pub fn search_retry(&self) -> RetryPolicy {
+        let search = &self.search;
+        retry_policy(search.retry_attempts, search.retry_base_delay_ms, search.retry_jitter, &search.retry_statuses)
+    }
+
+    pub fn fetch_retry(&self) -> RetryPolicy {
+        let fetch = &self.fetch;
+        retry_policy(fetch.retry_attempts, fetch.retry_base_delay_ms, fetch.retry_jitter, &fetch.retry_statuses)
+    }
+
+    /// Persistent cache settings, when a cache directory is configured
+    pub fn disk_cache(&self) -> Option<DiskCacheConfig> {
+        let cache = &self.cache;
+        cache.dir.as_ref().map(|dir| DiskCacheConfig {
+            max_bytes: cache.disk_max_bytes,
+            page_ttl: Duration::from_secs(cache.disk_page_ttl_secs),
+            search_ttl: Duration::from_secs(cache.disk_search_ttl_secs),
+            eviction: cache.disk_eviction,
+            ..DiskCacheConfig::new(dir)
+        })
+    }
+
+    pub fn ssrf_policy(&self) -> Result<SsrfPolicy> {
+        SsrfPolicy::with_allowlist(&self.fetch.allowlist)
+    }
+
+    pub fn domain_policy(&self) -> Result<DomainPolicy> {
+        DomainPolicy::with_patterns(&self.domains.allowed, &self.domains.blocked)
+    }
+}
+
+fn retry_policy(attempts: u32, base_delay_ms: u64, jitter: f64, statuses: &[u16]) -> RetryPolicy {
+    RetryPolicy {
+        jitter,
+        retryable_statuses: statuses.to_vec(),
+        ..RetryPolicy::new(attempts, Duration::from_millis(base_delay_ms))
+    }
+}
+
+fn env_value(raw: &str, kind: Kind) -> Result<Value> {
+    let raw = raw.trim();
+    Ok(match kind {
+        Kind::Text => Value::String(raw.to_string()),
+        Kind::Integer => Value::Integer(raw.parse().context("expected a whole number")?),
+        Kind::Float => Value::Float(raw.parse().context("expected a number")?),
+        Kind::Bool => Value::Boolean(match raw.to_ascii_lowercase().as_str() {
+            "1" | "true" | "yes" | "on" => true,
+            "0" | "false" | "no" | "off" | "" => false,
+            _ => anyhow::bail!("expected true or false"),
+        }),
+        Kind::List => Value::Array(
+            raw.split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| Value::String(item.to_string()))
+                .collect(),
+        ),
+        Kind::IntegerList => Value::Array(
+            raw.split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| Ok(Value::Integer(item.parse().context("expected whole numbers")?)))
+                .collect::<Result<_>>()?,
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests;
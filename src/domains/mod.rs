@@ -0,0 +1,161 @@
+//! Domain allowlist and denylist applied to search results and page fetches
+
+use anyhow::Result;
+use reqwest::Url;
+
+/// How a domain pattern matches a host
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DomainPattern {
+    /// `example.com`: that host only
+    Exact(String),
+    /// `.example.com`: the domain and every subdomain
+    Suffix(String),
+    /// `*.example.com`, `ads.*`: `*` matches any run of characters, dots included
+    Wildcard(String),
+}
+
+impl DomainPattern {
+    pub fn parse(pattern: &str) -> Result<Self> {
+        let pattern = normalize_host(pattern);
+        let valid = !pattern.trim_start_matches('.').is_empty()
+            && pattern
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '*'));
+        if !valid {
+            anyhow::bail!(
+                "Invalid domain pattern '{}': expected example.com, .example.com or *.example.com",
+                pattern
+            );
+        }
+
+        Ok(if pattern.contains('*') {
+            Self::Wildcard(pattern)
+        } else if let Some(domain) = pattern.strip_prefix('.') {
+            Self::Suffix(domain.to_string())
+        } else {
+            Self::Exact(pattern)
+        })
+    }
+
+    pub fn matches(&self, host: &str) -> bool {
+        let host = normalize_host(host);
+        match self {
+            Self::Exact(domain) => host == *domain,
+            Self::Suffix(domain) => {
+                host == *domain
+                    || host
+                        .strip_suffix(domain.as_str())
+                        .is_some_and(|sub| sub.ends_with('.'))
+            }
+            Self::Wildcard(pattern) => wildcard_match(pattern, &host),
+        }
+    }
+}
+
+impl std::fmt::Display for DomainPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Exact(domain) | Self::Wildcard(domain) => f.write_str(domain),
+            Self::Suffix(domain) => write!(f, ".{}", domain),
+        }
+    }
+}
+
+/// A host refused by the domain policy
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("{host} is blocked by the domain policy ({reason})")]
+pub struct DomainBlocked {
+    pub host: String,
+    pub reason: String,
+}
+
+/// Which domains may appear in search results and be fetched.
+///
+/// A host matching any denied pattern is blocked. When allowed patterns are
+/// configured, a host must also match one of them.
+#[derive(Debug, Clone, Default)]
+pub struct DomainPolicy {
+    allow: Vec<DomainPattern>,
+    deny: Vec<DomainPattern>,
+}
+
+impl DomainPolicy {
+    /// Policy that permits every domain
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Policy from allowed and denied patterns, rejecting any it cannot parse
+    pub fn with_patterns<A: AsRef<str>, D: AsRef<str>>(allow: &[A], deny: &[D]) -> Result<Self> {
+        let parse = |patterns: Vec<&str>| -> Result<Vec<DomainPattern>> {
+            patterns
+                .into_iter()
+                .map(str::trim)
+                .filter(|p| !p.is_empty())
+                .map(DomainPattern::parse)
+                .collect()
+        };
+        Ok(Self {
+            allow: parse(allow.iter().map(AsRef::as_ref).collect())?,
+            deny: parse(deny.iter().map(AsRef::as_ref).collect())?,
+        })
+    }
+
+    /// Whether no patterns are configured
+    pub fn is_empty(&self) -> bool {
+        self.allow.is_empty() && self.deny.is_empty()
+    }
+
+    /// Check a host against the denylist, then the allowlist
+    pub fn check_host(&self, host: &str) -> Result<(), DomainBlocked> {
+        let blocked = |reason: String| DomainBlocked {
+            host: normalize_host(host),
+            reason,
+        };
+        if let Some(pattern) = self.deny.iter().find(|p| p.matches(host)) {
+            return Err(blocked(format!("denied by '{}'", pattern)));
+        }
+        if !self.allow.is_empty() && !self.allow.iter().any(|p| p.matches(host)) {
+            return Err(blocked("not on the allowlist".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Check the host of a URL; URLs without a host only pass an empty allowlist
+    pub fn check_url(&self, url: &Url) -> Result<(), DomainBlocked> {
+        self.check_host(url.host_str().unwrap_or_default())
+    }
+
+    /// Whether a (possibly unparseable) URL string may be shown or fetched
+    pub fn permits(&self, url: &str) -> bool {
+        match Url::parse(url) {
+            Ok(url) => self.check_url(&url).is_ok(),
+            Err(_) => self.allow.is_empty(),
+        }
+    }
+}
+
+/// Glob match where `*` is the only special character
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !text.starts_with(first) || text.len() < first.len() + last.len() || !text.ends_with(last) {
+        return false;
+    }
+
+    let mut rest = &text[first.len()..text.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+fn normalize_host(host: &str) -> String {
+    host.trim().trim_end_matches('.').to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,83 @@
+//! Unit tests for the domain policy
+
+use super::*;
+
+fn pattern(p: &str) -> DomainPattern {
+    DomainPattern::parse(p).unwrap()
+}
+
+#[test]
+fn test_pattern_kinds() {
+    assert_eq!(pattern("Example.COM."), DomainPattern::Exact("example.com".to_string()));
+    assert_eq!(pattern(".example.com"), DomainPattern::Suffix("example.com".to_string()));
+    assert_eq!(pattern("*.example.com"), DomainPattern::Wildcard("*.example.com".to_string()));
+    assert_eq!(pattern(".example.com").to_string(), ".example.com");
+}
+
+#[test]
+fn test_pattern_rejects_invalid_input() {
+    for bad in ["", ".", "https://example.com", "exa mple.com"] {
+        assert!(DomainPattern::parse(bad).is_err(), "{}", bad);
+    }
+    let err = DomainPattern::parse("example.com/path").unwrap_err();
+    assert!(err.to_string().starts_with("Invalid domain pattern 'example.com/path'"));
+}
+
+#[test]
+fn test_exact_pattern() {
+    let exact = pattern("example.com");
+    assert!(exact.matches("example.com"));
+    assert!(exact.matches("EXAMPLE.com."));
+    assert!(!exact.matches("www.example.com"));
+    assert!(!exact.matches("badexample.com"));
+}
+
+#[test]
+fn test_suffix_pattern() {
+    let suffix = pattern(".example.com");
+    assert!(suffix.matches("example.com"));
+    assert!(suffix.matches("a.b.example.com"));
+    assert!(!suffix.matches("notexample.com"));
+    assert!(!suffix.matches("example.com.evil.net"));
+}
+
+#[test]
+fn test_wildcard_pattern() {
+    assert!(pattern("*.example.com").matches("www.example.com"));
+    assert!(!pattern("*.example.com").matches("example.com"));
+    assert!(pattern("ads.*").matches("ads.tracker.net"));
+    assert!(pattern("*content*farm*").matches("best-contentfarm.biz"));
+    assert!(!pattern("*content*farm*").matches("farm-content.biz"));
+    assert!(pattern("*").matches("anything.org"));
+}
+
+#[test]
+fn test_policy_denylist_wins() {
+    let policy = DomainPolicy::with_patterns(&[".example.com"], &["spam.example.com", "*.farm"]).unwrap();
+
+    assert!(policy.check_host("docs.example.com").is_ok());
+    assert_eq!(
+        policy.check_host("spam.example.com").unwrap_err().to_string(),
+        "spam.example.com is blocked by the domain policy (denied by 'spam.example.com')"
+    );
+    assert_eq!(
+        policy.check_host("Rust.org").unwrap_err().to_string(),
+        "rust.org is blocked by the domain policy (not on the allowlist)"
+    );
+    assert!(policy.check_host("content.farm").is_err());
+}
+
+#[test]
+fn test_policy_permits_urls() {
+    let deny_only = DomainPolicy::with_patterns::<&str, &str>(&[], &["*.pinterest.com"]).unwrap();
+    assert!(deny_only.permits("https://rust-lang.org/learn"));
+    assert!(!deny_only.permits("https://www.pinterest.com/pin/1"));
+    assert!(deny_only.permits("not a url"));
+
+    let allow_only = DomainPolicy::with_patterns::<&str, &str>(&["rust-lang.org"], &[]).unwrap();
+    assert!(allow_only.permits("https://rust-lang.org/"));
+    assert!(!allow_only.permits("not a url"));
+
+    assert!(DomainPolicy::new().is_empty());
+    assert!(DomainPolicy::new().permits("https://anything.example/"));
+}
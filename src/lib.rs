@@ -3,11 +3,31 @@
 //! A Model Context Protocol server that provides web search functionality
 //! using DuckDuckGo HTML scraping. Free, unlimited, no API keys required.
 
+pub mod cache;
+pub mod config;
+pub mod domains;
 pub mod models;
 pub mod search;
 pub mod mcp;
+pub mod ratelimit;
+pub mod retry;
+pub mod ssrf;
+
+#[cfg(test)]
+mod test_server;
 
 pub use models::{JsonRpcRequest, JsonRpcResponse, Tool, ToolContent, ToolResponse};
-pub use models::{SearchResult, SearchResponse, SearchParams};
-pub use search::DuckDuckGoScraper;
+pub use models::{SearchResult, SearchResponse, SearchParams, RetryInfo, FetchOptions, ContentFormat, ContentChunk, LengthUnit, PageMetadata, PageRanges, FetchResponse, Redirect};
+pub use models::{LinkScope, LinkOptions, PageLink, LinksResponse};
+pub use models::{SelectorOptions, SelectorMatches, SelectorResponse};
+pub use search::{
+    BodyError, DuckDuckGoScraper, ProxyConfig, RedirectError, RedirectPolicy, RobotsConfig, RobotsError,
+    RobotsTxt,
+};
 pub use mcp::McpServer;
+pub use config::Config;
+pub use domains::{DomainBlocked, DomainPattern, DomainPolicy};
+pub use cache::{CacheConfig, CacheStats, DiskCacheConfig, EvictionPolicy};
+pub use ratelimit::{HostRateLimiter, RateLedger, RateLimitConfig, RateLimiter};
+pub use retry::RetryPolicy;
+pub use ssrf::{SsrfError, SsrfPolicy};
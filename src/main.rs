@@ -1,9 +1,199 @@
 //! MCP Web Search Server - Main entry point
 
-use mcp_websearch::McpServer;
+use clap::{Parser, Subcommand, ValueEnum};
+use mcp_websearch::models::{ContentFormat, LengthUnit};
+use mcp_websearch::{Config, McpServer};
+use serde_json::json;
+use std::io::{self, Write};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+
+#[derive(Parser)]
+#[command(name = "mcp-websearch", version, about = "MCP web search server using DuckDuckGo")]
+struct Cli {
+    /// TOML config file (default: $MCP_WEBSEARCH_CONFIG)
+    #[arg(short, long, global = true, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the MCP server (the default, on stdio)
+    Serve {
+        /// Serve streamable HTTP on this address instead of stdio
+        #[arg(long, value_name = "ADDR", num_args = 0..=1, default_missing_value = "127.0.0.1:8080")]
+        http: Option<SocketAddr>,
+    },
+    /// Search DuckDuckGo and print the results
+    Search {
+        query: String,
+        /// Results to return
+        #[arg(short, long, default_value_t = 10)]
+        limit: u64,
+        /// Results to skip, for paging
+        #[arg(long, default_value_t = 0)]
+        offset: u64,
+        /// Bypass cached results
+        #[arg(long)]
+        no_cache: bool,
+        #[arg(short, long, value_enum, default_value_t = Output::Text)]
+        output: Output,
+    },
+    /// Fetch a page or document and print its content
+    Fetch {
+        url: String,
+        /// How HTML pages are rendered
+        #[arg(short, long, value_enum, default_value_t = Format::Markdown)]
+        format: Format,
+        /// Character offset to start reading at
+        #[arg(long)]
+        start_index: Option<u64>,
+        /// Length to return, in --length-unit (default: fetch.default_max_length characters)
+        #[arg(long)]
+        max_length: Option<u64>,
+        /// Unit of --max-length
+        #[arg(long, value_enum, default_value_t = Unit::Chars)]
+        length_unit: Unit,
+        /// Pages to read from a PDF, e.g. 1-3,5
+        #[arg(long)]
+        pages: Option<String>,
+        /// JSONPath expression for JSON documents
+        #[arg(long)]
+        json_path: Option<String>,
+        /// Bypass the cached copy of the page
+        #[arg(long)]
+        no_cache: bool,
+        #[arg(short, long, value_enum, default_value_t = Output::Text)]
+        output: Output,
+    },
+    /// Print the tool schemas served to MCP clients
+    Tools,
+}
+
+/// How one-shot commands print their result
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Output {
+    /// The text an LLM client receives
+    Text,
+    /// The full tool result (content and structured content) as JSON
+    Json,
+}
+
+/// `--format` values, mapped onto the tool's `ContentFormat`
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    /// Main content as Markdown: headings, lists, code, tables and links kept
+    #[value(alias = "md")]
+    Markdown,
+    /// Main content as plain text
+    Text,
+    /// The page's HTML, unprocessed
+    Html,
+}
+
+impl From<Format> for ContentFormat {
+    fn from(format: Format) -> Self {
+        match format {
+            Format::Markdown => Self::Markdown,
+            Format::Text => Self::Text,
+            Format::Html => Self::Html,
+        }
+    }
+}
+
+/// `--length-unit` values, mapped onto the tool's `LengthUnit`
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Unit {
+    /// Characters
+    Chars,
+    /// Approximate tokens (4 characters each)
+    Tokens,
+}
+
+impl From<Unit> for LengthUnit {
+    fn from(unit: Unit) -> Self {
+        match unit {
+            Unit::Chars => Self::Chars,
+            Unit::Tokens => Self::Tokens,
+        }
+    }
+}
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let server = McpServer::new();
-    server.run().await
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    // Settings come from the config file, then MCP_WEBSEARCH_* overrides; bad values stop startup
+    let server = match Config::load(cli.config.as_deref()).and_then(|config| McpServer::from_config(&config)) {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("Error: {:#}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let result = match cli.command.unwrap_or(Command::Serve { http: None }) {
+        Command::Serve { http: None } => server.run().await.map_err(|e| e.to_string()),
+        Command::Serve { http: Some(addr) } => serve_http(server, addr).await,
+        Command::Tools => print(&serde_json::to_string_pretty(&server.get_tools()).unwrap_or_default()),
+        Command::Search { query, limit, offset, no_cache, output } => {
+            let params = json!({"query": query, "limit": limit, "offset": offset, "no_cache": no_cache});
+            call(&server, "web_search", params, output).await
+        }
+        Command::Fetch { url, format, start_index, max_length, length_unit, pages, json_path, no_cache, output } => {
+            let params = json!({
+                "url": url,
+                "format": ContentFormat::from(format),
+                "start_index": start_index,
+                "max_length": max_length,
+                "length_unit": LengthUnit::from(length_unit),
+                "pages": pages,
+                "json_path": json_path,
+                "no_cache": no_cache,
+            });
+            call(&server, "fetch_content", params, output).await
+        }
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn serve_http(server: McpServer, addr: SocketAddr) -> Result<(), String> {
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .map_err(|e| format!("Could not listen on {}: {}", addr, e))?;
+    Arc::new(server).run_http(listener).await.map_err(|e| e.to_string())
+}
+
+/// Run one tool call the way an MCP client would and print its result
+async fn call(server: &McpServer, tool: &str, params: serde_json::Value, output: Output) -> Result<(), String> {
+    let response = server.call_tool(tool, &params).await.map_err(|e| format!("{:#}", e))?;
+    let text = response.content.first().map(|c| c.text.as_str()).unwrap_or_default();
+    if response.is_error == Some(true) {
+        return Err(text.to_string());
+    }
+
+    match output {
+        Output::Text => print(text),
+        Output::Json => print(&serde_json::to_string_pretty(&response).unwrap_or_default()),
+    }
+}
+
+/// Print to stdout, treating a closed pipe (e.g. `| head`) as success
+fn print(text: &str) -> Result<(), String> {
+    match writeln!(io::stdout().lock(), "{}", text) {
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => Err(e.to_string()),
+        _ => Ok(()),
+    }
 }
@@ -0,0 +1,113 @@
+//! Streamable HTTP transport: one JSON-RPC message POSTed to `/mcp` per request
+
+use super::McpServer;
+use crate::models::{JsonRpcError, JsonRpcRequest, JsonRpcResponse};
+use http_body_util::{BodyExt, Full, LengthLimitError, Limited};
+use hyper::body::{Bytes, Incoming};
+use hyper::header::{ALLOW, CONTENT_TYPE, HeaderValue, ORIGIN};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use std::convert::Infallible;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+/// Path of the MCP endpoint
+pub const MCP_PATH: &str = "/mcp";
+
+/// Largest JSON-RPC message accepted
+const MAX_REQUEST_BODY: usize = 1024 * 1024;
+
+impl McpServer {
+    /// Serve MCP over HTTP on a bound listener
+    pub async fn run_http(self: Arc<Self>, listener: TcpListener) -> std::io::Result<()> {
+        eprintln!(
+            "MCP Web Search Server (Rust) listening on http://{}{}",
+            listener.local_addr()?,
+            MCP_PATH
+        );
+
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let server = self.clone();
+            tokio::spawn(async move {
+                let service = service_fn(move |req| {
+                    let server = server.clone();
+                    async move { Ok::<_, Infallible>(server.handle_http(req).await) }
+                });
+                if let Err(e) = http1::Builder::new().serve_connection(TokioIo::new(stream), service).await {
+                    eprintln!("HTTP connection error: {}", e);
+                }
+            });
+        }
+    }
+
+    async fn handle_http(&self, req: Request<Incoming>) -> Response<Full<Bytes>> {
+        if req.uri().path() != MCP_PATH {
+            return plain(StatusCode::NOT_FOUND, "Not found");
+        }
+        // Browsers send Origin; refusing foreign ones stops DNS rebinding attacks on a local server
+        if !origin_allowed(req.headers().get(ORIGIN)) {
+            return plain(StatusCode::FORBIDDEN, "Origin not allowed");
+        }
+        if req.method() != Method::POST {
+            let mut response = plain(StatusCode::METHOD_NOT_ALLOWED, "Use POST");
+            response.headers_mut().insert(ALLOW, HeaderValue::from_static("POST"));
+            return response;
+        }
+
+        let body = match Limited::new(req.into_body(), MAX_REQUEST_BODY).collect().await {
+            Ok(body) => body.to_bytes(),
+            Err(e) if e.is::<LengthLimitError>() => {
+                return plain(StatusCode::PAYLOAD_TOO_LARGE, "Request body too large");
+            }
+            Err(_) => return plain(StatusCode::BAD_REQUEST, "Could not read request body"),
+        };
+
+        let request: JsonRpcRequest = match serde_json::from_slice(&body) {
+            Ok(request) => request,
+            Err(e) => {
+                return json(&JsonRpcResponse {
+                    jsonrpc: "2.0".to_string(),
+                    id: None,
+                    result: None,
+                    error: Some(JsonRpcError::new(-32700, format!("Parse error: {}", e))),
+                });
+            }
+        };
+
+        // Notifications are acknowledged without a body
+        if request.id.is_none() {
+            return plain(StatusCode::ACCEPTED, "");
+        }
+        json(&self.handle_request(request).await)
+    }
+}
+
+/// Requests without an Origin header (non-browser clients) and local origins are allowed
+fn origin_allowed(origin: Option<&HeaderValue>) -> bool {
+    let Some(origin) = origin else {
+        return true;
+    };
+    origin
+        .to_str()
+        .ok()
+        .and_then(|origin| reqwest::Url::parse(origin).ok())
+        .is_some_and(|url| matches!(url.host_str(), Some("localhost" | "127.0.0.1" | "[::1]")))
+}
+
+fn plain(status: StatusCode, text: &'static str) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::new(Bytes::from_static(text.as_bytes())));
+    *response.status_mut() = status;
+    response
+}
+
+fn json(body: &JsonRpcResponse) -> Response<Full<Bytes>> {
+    let bytes = serde_json::to_vec(body).unwrap_or_default();
+    let mut response = Response::new(Full::new(Bytes::from(bytes)));
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    response
+}
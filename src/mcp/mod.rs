@@ -1,10 +1,24 @@
 //! MCP (Model Context Protocol) server implementation
 
-use crate::models::{JsonRpcRequest, JsonRpcResponse, ToolContent, ToolResponse};
+mod http;
+
+use crate::config::Config;
+use crate::models::{
+    ContentFormat, FetchOptions, JsonRpcRequest, JsonRpcResponse, LengthUnit, LinkOptions,
+    LinkScope, PageRanges, SelectorOptions, ToolContent, ToolResponse,
+};
+use crate::ratelimit::RateLimitConfig;
 use crate::search::DuckDuckGoScraper;
 use serde_json::json;
 use std::io::{self, BufRead, BufReader, Write};
 
+pub use http::MCP_PATH;
+
+/// Accepted values of enum arguments, for error messages
+const FORMATS: &str = "markdown, text or html";
+const UNITS: &str = "chars or tokens";
+const SCOPES: &str = "all, internal or external";
+
 /// MCP server
 pub struct McpServer {
     scraper: DuckDuckGoScraper,
@@ -13,18 +27,29 @@ pub struct McpServer {
 impl McpServer {
     /// Create a new MCP server instance
     pub fn new() -> Self {
-        Self {
-            scraper: DuckDuckGoScraper::new(),
-        }
+        Self::with_scraper(DuckDuckGoScraper::new())
+    }
+
+    /// Create a server from a validated configuration
+    pub fn from_config(config: &Config) -> Result<Self, anyhow::Error> {
+        Ok(Self::with_scraper(DuckDuckGoScraper::from_config(config)?))
+    }
+
+    /// Create a server around a preconfigured scraper
+    pub fn with_scraper(scraper: DuckDuckGoScraper) -> Self {
+        Self { scraper }
     }
 
     /// Get available tools
     pub fn get_tools(&self) -> serde_json::Value {
+        let max_length = self.scraper.default_max_length();
+        let search_limits = describe_limits(self.scraper.search_limits());
+        let host_limits = describe_limits(self.scraper.host_limits());
         json!({
             "tools": [
                 {
                     "name": "web_search",
-                    "description": "Search the web using DuckDuckGo. Returns formatted results with title, URL, and summary in natural language. Rate limited to 20 requests/minute with 3 second minimum delay between requests to avoid blocking.",
+                    "description": format!("Search the web using DuckDuckGo. Returns formatted results with title, URL, and summary in natural language. Repeated queries are served from a short-lived cache. Rate limited to {} between requests to avoid blocking.", search_limits),
                     "inputSchema": {
                         "type": "object",
                         "properties": {
@@ -44,6 +69,11 @@ impl McpServer {
                                 "description": "Pagination offset (default: 0)",
                                 "minimum": 0,
                                 "default": 0
+                            },
+                            "no_cache": {
+                                "type": "boolean",
+                                "description": "Bypass cached results for this query (default: false)",
+                                "default": false
                             }
                         },
                         "required": ["query"]
@@ -51,17 +81,134 @@ impl McpServer {
                 },
                 {
                     "name": "fetch_content",
-                    "description": "Fetch and parse the content of a webpage. Extracts the main text content from HTML, removing scripts, styles, and navigation elements. Useful for reading full articles or pages found via search. Rate limited to 20 requests/minute with 3 second minimum delay between requests to avoid blocking.",
+                    "description": format!("Fetch and parse the content of a webpage. Extracts the main content from HTML, removing navigation, sidebars, and other boilerplate, and returns it as Markdown (headings, lists, code blocks, tables, and links preserved), plain text, or raw HTML. Long pages are returned in windows; the response reports the total length and the start_index of the next window. Page metadata (title, canonical URL, author, publish date, language, OpenGraph and JSON-LD) is included for citing sources. PDFs are converted to text page by page, with their page count and document metadata. JSON is pretty-printed (optionally narrowed with a JSONPath), RSS and Atom feeds are listed entry by entry, plain text and Markdown are returned as-is, and binary files such as images and archives are refused. Useful for reading full articles or pages found via search. Pages are cached briefly. Rate limited per website ({} per host), independently of web_search.", host_limits),
                     "inputSchema": {
                         "type": "object",
                         "properties": {
                             "url": {
                                 "type": "string",
                                 "description": "The URL of the webpage to fetch and parse"
+                            },
+                            "format": {
+                                "type": "string",
+                                "enum": ["markdown", "text", "html"],
+                                "description": "Output format: markdown keeps document structure, text is plain text, html is the unprocessed page (default: markdown)",
+                                "default": "markdown"
+                            },
+                            "start_index": {
+                                "type": "integer",
+                                "description": "Character offset to start reading from; use the next start_index reported in the previous response to continue a long page (default: 0)",
+                                "minimum": 0,
+                                "default": 0
+                            },
+                            "max_length": {
+                                "type": "integer",
+                                "description": format!("Maximum length of the returned content, in length_unit (default: {} characters)", max_length),
+                                "minimum": 1,
+                                "default": max_length
+                            },
+                            "length_unit": {
+                                "type": "string",
+                                "enum": ["chars", "tokens"],
+                                "description": "Unit of max_length: chars, or tokens (approximately 4 characters each) (default: chars)",
+                                "default": "chars"
+                            },
+                            "pages": {
+                                "type": "string",
+                                "description": "PDF only: pages to read, e.g. \"1-3,5\" or \"10-\" (default: all pages)"
+                            },
+                            "json_path": {
+                                "type": "string",
+                                "description": "JSON only: JSONPath expression selecting the values to return, e.g. \"$.items[*].name\" (default: the whole document)"
+                            },
+                            "no_cache": {
+                                "type": "boolean",
+                                "description": "Bypass the cached copy of this page (default: false)",
+                                "default": false
+                            }
+                        },
+                        "required": ["url"]
+                    }
+                },
+                {
+                    "name": "extract_links",
+                    "description": "List the links on a webpage as absolute URLs with their anchor text, rel attributes, and whether they are internal (same site) or external. Useful for navigating documentation sites or finding related pages. Shares the per-website rate limit and page cache with fetch_content.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "url": {
+                                "type": "string",
+                                "description": "The URL of the webpage whose links to list"
+                            },
+                            "scope": {
+                                "type": "string",
+                                "enum": ["all", "internal", "external"],
+                                "description": "Only links to the same site (internal), to other sites (external), or both (default: all)",
+                                "default": "all"
+                            },
+                            "pattern": {
+                                "type": "string",
+                                "description": "Only links whose URL contains this text; * matches any characters (e.g. \"/docs/*/api\")"
+                            },
+                            "limit": {
+                                "type": "integer",
+                                "description": "Maximum number of links to return (1-500, default: 100)",
+                                "minimum": 1,
+                                "maximum": 500,
+                                "default": 100
+                            },
+                            "no_cache": {
+                                "type": "boolean",
+                                "description": "Bypass the cached copy of this page (default: false)",
+                                "default": false
                             }
                         },
                         "required": ["url"]
                     }
+                },
+                {
+                    "name": "fetch_selector",
+                    "description": "Fetch a webpage and return only the elements matching one or more CSS selectors (e.g. \"#api-reference\", \"table.changelog\", \"h2 + p\"), rendered as Markdown, plain text, or HTML. Use when you know which part of a page you need. Shares the per-website rate limit and page cache with fetch_content.",
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": {
+                            "url": {
+                                "type": "string",
+                                "description": "The URL of the webpage to fetch"
+                            },
+                            "selectors": {
+                                "type": "array",
+                                "items": { "type": "string" },
+                                "minItems": 1,
+                                "description": "CSS selectors; matches are reported separately for each"
+                            },
+                            "format": {
+                                "type": "string",
+                                "enum": ["markdown", "text", "html"],
+                                "description": "Output format for each match (default: markdown)",
+                                "default": "markdown"
+                            },
+                            "limit": {
+                                "type": "integer",
+                                "description": "Maximum matches returned per selector (1-100, default: 10)",
+                                "minimum": 1,
+                                "maximum": 100,
+                                "default": 10
+                            },
+                            "max_length": {
+                                "type": "integer",
+                                "description": format!("Maximum characters of content across all matches (default: {})", max_length),
+                                "minimum": 1,
+                                "default": max_length
+                            },
+                            "no_cache": {
+                                "type": "boolean",
+                                "description": "Bypass the cached copy of this page (default: false)",
+                                "default": false
+                            }
+                        },
+                        "required": ["url", "selectors"]
+                    }
                 }
             ]
         })
@@ -93,12 +240,99 @@ impl McpServer {
                     .as_str()
                     .ok_or_else(|| anyhow::anyhow!("Missing 'url' parameter"))?;
 
-                let content = self.scraper.fetch_content(url).await?;
+                let options = FetchOptions {
+                    no_cache: params["no_cache"].as_bool().unwrap_or(false),
+                    format: enum_param(params, "format", ContentFormat::parse, FORMATS)?,
+                    start_index: params["start_index"].as_u64().unwrap_or(0) as usize,
+                    max_length: params["max_length"].as_u64().map(|n| n as usize),
+                    length_unit: enum_param(params, "length_unit", LengthUnit::parse, UNITS)?,
+                    pages: params["pages"]
+                        .as_str()
+                        .map(|value| {
+                            PageRanges::parse(value).ok_or_else(|| {
+                                anyhow::anyhow!(
+                                    "Invalid 'pages' parameter '{}': expected pages or ranges like 1-3,5",
+                                    value
+                                )
+                            })
+                        })
+                        .transpose()?,
+                    json_path: params["json_path"].as_str().map(str::to_string),
+                };
+
+                let response = self.scraper.fetch_page(url, &options).await?;
 
                 Ok(ToolResponse {
+                    structured_content: serde_json::to_value(&response).ok(),
                     content: vec![ToolContent {
                         content_type: "text".to_string(),
-                        text: content,
+                        text: response.text,
+                    }],
+                    is_error: None,
+                })
+            }
+            "extract_links" => {
+                let url = params["url"]
+                    .as_str()
+                    .ok_or_else(|| anyhow::anyhow!("Missing 'url' parameter"))?;
+
+                let options = LinkOptions {
+                    scope: enum_param(params, "scope", LinkScope::parse, SCOPES)?,
+                    pattern: params["pattern"]
+                        .as_str()
+                        .filter(|p| !p.trim().is_empty())
+                        .map(str::to_string),
+                    limit: params["limit"].as_u64().unwrap_or(100).clamp(1, 500) as usize,
+                    no_cache: params["no_cache"].as_bool().unwrap_or(false),
+                };
+
+                let response = self.scraper.extract_links(url, &options).await?;
+
+                Ok(ToolResponse {
+                    structured_content: serde_json::to_value(&response).ok(),
+                    content: vec![ToolContent {
+                        content_type: "text".to_string(),
+                        text: self.scraper.format_links_for_llm(&response),
+                    }],
+                    is_error: None,
+                })
+            }
+            "fetch_selector" => {
+                let url = params["url"]
+                    .as_str()
+                    .ok_or_else(|| anyhow::anyhow!("Missing 'url' parameter"))?;
+
+                // A single selector string is accepted as well as a list
+                let selectors: Vec<String> = match &params["selectors"] {
+                    serde_json::Value::String(selector) => vec![selector.clone()],
+                    serde_json::Value::Array(items) => items
+                        .iter()
+                        .map(|item| {
+                            item.as_str().map(str::to_string).ok_or_else(|| {
+                                anyhow::anyhow!("Invalid 'selectors' parameter: expected strings")
+                            })
+                        })
+                        .collect::<Result<_, _>>()?,
+                    _ => anyhow::bail!("Missing 'selectors' parameter"),
+                };
+
+                let options = SelectorOptions {
+                    selectors,
+                    format: enum_param(params, "format", ContentFormat::parse, FORMATS)?,
+                    limit: params["limit"].as_u64().unwrap_or(10).clamp(1, 100) as usize,
+                    max_length: params["max_length"]
+                        .as_u64()
+                        .map_or(self.scraper.default_max_length(), |n| n as usize),
+                    no_cache: params["no_cache"].as_bool().unwrap_or(false),
+                };
+
+                let response = self.scraper.fetch_selector(url, &options).await?;
+
+                Ok(ToolResponse {
+                    structured_content: serde_json::to_value(&response).ok(),
+                    content: vec![ToolContent {
+                        content_type: "text".to_string(),
+                        text: self.scraper.format_selector_results_for_llm(&response),
                     }],
                     is_error: None,
                 })
@@ -111,8 +345,10 @@ impl McpServer {
                 let limit = params["limit"].as_u64().unwrap_or(10) as usize;
                 let offset = params["offset"].as_u64().unwrap_or(0) as usize;
 
+                let no_cache = params["no_cache"].as_bool().unwrap_or(false);
+
                 let search_params =
-                    crate::models::SearchParams::new(query, limit, offset);
+                    crate::models::SearchParams::new(query, limit, offset).with_no_cache(no_cache);
 
                 let response = self.scraper.search(&search_params).await?;
 
@@ -125,6 +361,7 @@ impl McpServer {
                         text: formatted,
                     }],
                     is_error: None,
+                    structured_content: None,
                 })
             }
             _ => Ok(ToolResponse {
@@ -133,6 +370,7 @@ impl McpServer {
                     text: format!("Unknown tool: {}", name),
                 }],
                 is_error: Some(true),
+                structured_content: None,
             }),
         }
     }
@@ -148,14 +386,26 @@ impl McpServer {
 
                     match self.call_tool(name, arguments).await {
                         Ok(r) => (Some(json!(r)), None),
-                        Err(e) => (None, Some(crate::models::JsonRpcError::new(-32600, e.to_string()))),
+                        Err(e) => (
+                            None,
+                            Some(crate::models::JsonRpcError::new(-32600, e.to_string())),
+                        ),
                     }
                 } else {
-                    (None, Some(crate::models::JsonRpcError::new(-32600, "Missing params")))
+                    (
+                        None,
+                        Some(crate::models::JsonRpcError::new(-32600, "Missing params")),
+                    )
                 }
             }
             "initialize" => (Some(self.handle_initialize()), None),
-            _ => (None, Some(crate::models::JsonRpcError::new(-32600, format!("Unknown method: {}", request.method)))),
+            _ => (
+                None,
+                Some(crate::models::JsonRpcError::new(
+                    -32600,
+                    format!("Unknown method: {}", request.method),
+                )),
+            ),
         };
 
         JsonRpcResponse {
@@ -200,6 +450,39 @@ impl McpServer {
     }
 }
 
+/// Rate limit in tool descriptions, e.g. "20 requests/minute with 3 second minimum delay"
+fn describe_limits(limits: &RateLimitConfig) -> String {
+    let rate = format!("{} requests/minute", limits.max_requests_per_minute);
+    let delay = limits.min_delay;
+    if delay.is_zero() {
+        rate
+    } else if delay.subsec_millis() == 0 {
+        format!("{} with {} second minimum delay", rate, delay.as_secs())
+    } else {
+        format!("{} with {} ms minimum delay", rate, delay.as_millis())
+    }
+}
+
+/// Optional string argument parsed into an enum, defaulting when absent
+fn enum_param<T: Default>(
+    params: &serde_json::Value,
+    name: &str,
+    parse: fn(&str) -> Option<T>,
+    expected: &str,
+) -> anyhow::Result<T> {
+    match params[name].as_str() {
+        Some(value) => parse(value).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Invalid '{}' parameter '{}': expected {}",
+                name,
+                value,
+                expected
+            )
+        }),
+        None => Ok(T::default()),
+    }
+}
+
 impl Default for McpServer {
     fn default() -> Self {
         Self::new()
@@ -1,139 +1,258 @@
 //! Unit tests for MCP module
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::models::{JsonRpcRequest, SearchParams};
-    use serde_json::json;
-
-    #[tokio::test]
-    async fn test_get_tools() {
-        let server = McpServer::new();
-        let tools = server.get_tools();
-
-        assert!(tools["tools"].is_array());
-        let tools_array = tools["tools"].as_array().unwrap();
-        assert_eq!(tools_array.len(), 1);
-
-        let tool = &tools_array[0];
-        assert_eq!(tool["name"], "web_search");
-        assert!(tool["description"].is_string());
-        assert!(tool["inputSchema"]["properties"]["query"]["type"] == "string");
-    }
+use super::*;
+use crate::models::JsonRpcRequest;
+use serde_json::json;
 
-    #[tokio::test]
-    async fn test_handle_initialize() {
-        let server = McpServer::new();
-        let result = server.handle_initialize();
+#[tokio::test]
+async fn test_get_tools() {
+    let server = McpServer::new();
+    let tools = server.get_tools();
 
-        assert_eq!(result["protocolVersion"], "2024-11-05");
-        assert_eq!(result["serverInfo"]["name"], "mcp-websearch");
-        assert_eq!(result["serverInfo"]["version"], "1.0.0");
-    }
+    assert!(tools["tools"].is_array());
+    let tools_array = tools["tools"].as_array().unwrap();
+    assert_eq!(tools_array.len(), 4);
 
-    #[tokio::test]
-    async fn test_handle_request_tools_list() {
-        let server = McpServer::new();
-        let request = JsonRpcRequest {
-            jsonrpc: "2.0".to_string(),
-            id: Some(json!(1)),
-            method: "tools/list".to_string(),
-            params: None,
-        };
-
-        let response = server.handle_request(request).await;
-
-        assert_eq!(response.jsonrpc, "2.0");
-        assert_eq!(response.id, Some(json!(1)));
-        assert!(response.result.is_some());
-        assert!(response.error.is_none());
-    }
+    let tool = &tools_array[0];
+    assert_eq!(tool["name"], "web_search");
+    assert!(tool["description"].is_string());
+    assert!(tool["inputSchema"]["properties"]["query"]["type"] == "string");
+}
 
-    #[tokio::test]
-    async fn test_handle_request_initialize() {
-        let server = McpServer::new();
-        let request = JsonRpcRequest {
-            jsonrpc: "2.0".to_string(),
-            id: Some(json!(1)),
-            method: "initialize".to_string(),
-            params: None,
-        };
-
-        let response = server.handle_request(request).await;
-
-        assert_eq!(response.jsonrpc, "2.0");
-        assert!(response.result.is_some());
-        assert_eq!(response.result.unwrap()["serverInfo"]["name"], "mcp-websearch");
-    }
+#[tokio::test]
+async fn test_handle_initialize() {
+    let server = McpServer::new();
+    let result = server.handle_initialize();
 
-    #[tokio::test]
-    async fn test_handle_request_unknown_method() {
-        let server = McpServer::new();
-        let request = JsonRpcRequest {
-            jsonrpc: "2.0".to_string(),
-            id: Some(json!(1)),
-            method: "unknown/method".to_string(),
-            params: None,
-        };
-
-        let response = server.handle_request(request).await;
-
-        assert!(response.result.is_none());
-        assert!(response.error.is_some());
-        assert_eq!(response.error.unwrap().code, -32600);
-    }
+    assert_eq!(result["protocolVersion"], "2024-11-05");
+    assert_eq!(result["serverInfo"]["name"], "mcp-websearch");
+    assert_eq!(result["serverInfo"]["version"], "1.0.0");
+}
 
-    #[tokio::test]
-    async fn test_call_tool_unknown_tool() {
-        let server = McpServer::new();
-        let params = json!({"query": "test"});
+#[tokio::test]
+async fn test_handle_request_tools_list() {
+    let server = McpServer::new();
+    let request = JsonRpcRequest {
+        jsonrpc: "2.0".to_string(),
+        id: Some(json!(1)),
+        method: "tools/list".to_string(),
+        params: None,
+    };
 
-        let result = server.call_tool("unknown_tool", &params).await.unwrap();
+    let response = server.handle_request(request).await;
 
-        assert_eq!(result.content.len(), 1);
-        assert!(result.content[0].text.contains("Unknown tool"));
-        assert_eq!(result.is_error, Some(true));
-    }
+    assert_eq!(response.jsonrpc, "2.0");
+    assert_eq!(response.id, Some(json!(1)));
+    assert!(response.result.is_some());
+    assert!(response.error.is_none());
+}
 
-    #[tokio::test]
-    async fn test_call_tool_web_search_missing_query() {
-        let server = McpServer::new();
-        let params = json!({}); // Missing query
+#[tokio::test]
+async fn test_handle_request_initialize() {
+    let server = McpServer::new();
+    let request = JsonRpcRequest {
+        jsonrpc: "2.0".to_string(),
+        id: Some(json!(1)),
+        method: "initialize".to_string(),
+        params: None,
+    };
 
-        let result = server.call_tool("web_search", &params).await;
+    let response = server.handle_request(request).await;
 
-        assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("Missing 'query'"));
-    }
+    assert_eq!(response.jsonrpc, "2.0");
+    assert!(response.result.is_some());
+    assert_eq!(response.result.unwrap()["serverInfo"]["name"], "mcp-websearch");
+}
+
+#[tokio::test]
+async fn test_handle_request_unknown_method() {
+    let server = McpServer::new();
+    let request = JsonRpcRequest {
+        jsonrpc: "2.0".to_string(),
+        id: Some(json!(1)),
+        method: "unknown/method".to_string(),
+        params: None,
+    };
+
+    let response = server.handle_request(request).await;
+
+    assert!(response.result.is_none());
+    assert!(response.error.is_some());
+    assert_eq!(response.error.unwrap().code, -32600);
+}
+
+#[tokio::test]
+async fn test_call_tool_unknown_tool() {
+    let server = McpServer::new();
+    let params = json!({"query": "test"});
 
-    #[tokio::test]
-    async fn test_call_tool_web_search_valid_params() {
-        let server = McpServer::new();
-        let params = json!({
-            "query": "test query",
-            "limit": 5,
-            "offset": 0
-        });
-
-        // This will actually make a network request, so we just verify it doesn't error immediately
-        let result = server.call_tool("web_search", &params).await;
-
-        // Result could be Ok or Err depending on network, but should not panic
-        match result {
-            Ok(response) => {
-                assert_eq!(response.content.len(), 1);
-                assert_eq!(response.is_error, None);
-            }
-            Err(_) => {
-                // Network errors are acceptable in tests
-            }
+    let result = server.call_tool("unknown_tool", &params).await.unwrap();
+
+    assert_eq!(result.content.len(), 1);
+    assert!(result.content[0].text.contains("Unknown tool"));
+    assert_eq!(result.is_error, Some(true));
+}
+
+#[tokio::test]
+async fn test_call_tool_web_search_missing_query() {
+    let server = McpServer::new();
+    let params = json!({}); // Missing query
+
+    let result = server.call_tool("web_search", &params).await;
+
+    assert!(result.is_err());
+    assert!(result.unwrap_err().to_string().contains("Missing 'query'"));
+}
+
+#[tokio::test]
+async fn test_call_tool_web_search_valid_params() {
+    let server = McpServer::new();
+    let params = json!({
+        "query": "test query",
+        "limit": 5,
+        "offset": 0
+    });
+
+    // This will actually make a network request, so we just verify it doesn't error immediately
+    let result = server.call_tool("web_search", &params).await;
+
+    // Result could be Ok or Err depending on network, but should not panic
+    match result {
+        Ok(response) => {
+            assert_eq!(response.content.len(), 1);
+            assert_eq!(response.is_error, None);
+        }
+        Err(_) => {
+            // Network errors are acceptable in tests
         }
     }
+}
 
-    #[test]
-    fn test_default_server() {
-        let server = McpServer::default();
-        // Just verify it can be created
-        assert!(server.scraper.client.timeout().is_some());
-    }
+#[test]
+fn test_default_server() {
+    let server = McpServer::default();
+    // Just verify it can be created
+    assert!(server.get_tools()["tools"].is_array());
+}
+
+#[tokio::test]
+async fn test_call_tool_fetch_content_invalid_format() {
+    let server = McpServer::new();
+    let params = json!({"url": "https://example.com", "format": "pdf"});
+
+    let result = server.call_tool("fetch_content", &params).await;
+
+    let err = result.unwrap_err().to_string();
+    assert!(err.contains("Invalid 'format'"));
+    assert!(err.contains("markdown, text or html"));
+}
+
+#[tokio::test]
+async fn test_call_tool_fetch_content_invalid_json_path() {
+    let server = McpServer::new();
+    let params = json!({"url": "https://example.com/data.json", "json_path": "items[0]"});
+
+    let result = server.call_tool("fetch_content", &params).await;
+
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Invalid JSONPath 'items[0]': must start with '$'"
+    );
+}
+
+#[tokio::test]
+async fn test_call_tool_extract_links_invalid_scope() {
+    let server = McpServer::new();
+    let params = json!({"url": "https://example.com", "scope": "nearby"});
+
+    let result = server.call_tool("extract_links", &params).await;
+
+    assert!(result.unwrap_err().to_string().contains("Invalid 'scope'"));
+}
+
+#[tokio::test]
+async fn test_call_tool_fetch_selector_invalid_selector() {
+    let server = McpServer::new();
+    let params = json!({"url": "https://example.com", "selectors": ["main", "div[[x"]});
+
+    let result = server.call_tool("fetch_selector", &params).await;
+
+    assert!(result.unwrap_err().to_string().contains("Invalid CSS selector 'div[[x'"));
+}
+
+#[tokio::test]
+async fn test_call_tool_fetch_selector_missing_selectors() {
+    let server = McpServer::new();
+    let params = json!({"url": "https://example.com"});
+
+    let result = server.call_tool("fetch_selector", &params).await;
+
+    assert!(result.unwrap_err().to_string().contains("Missing 'selectors'"));
+}
+
+#[test]
+fn test_from_config_applies_default_max_length() {
+    let config = Config::from_sources(Some("[fetch]\ndefault_max_length = 2500\n"), |_| None).unwrap();
+    let server = McpServer::from_config(&config).unwrap();
+
+    let tools = server.get_tools();
+    let fetch = &tools["tools"][1]["inputSchema"]["properties"]["max_length"];
+    assert_eq!(fetch["default"], 2500);
+    assert!(fetch["description"].as_str().unwrap().contains("default: 2500 characters"));
+}
+
+#[test]
+fn test_tool_descriptions_report_rate_limits() {
+    let default_tools = McpServer::new().get_tools();
+    let search = default_tools["tools"][0]["description"].as_str().unwrap();
+    assert!(search.contains("20 requests/minute with 3 second minimum delay"));
+
+    let config = Config::from_sources(
+        Some("[search]\nrequests_per_minute = 10\nmin_delay_ms = 500\n\n[fetch]\nrequests_per_minute = 40\nmin_delay_ms = 2000\n"),
+        |_| None,
+    )
+    .unwrap();
+    let tools = McpServer::from_config(&config).unwrap().get_tools();
+
+    let search = tools["tools"][0]["description"].as_str().unwrap();
+    assert!(search.contains("10 requests/minute with 500 ms minimum delay"));
+    let fetch = tools["tools"][1]["description"].as_str().unwrap();
+    assert!(fetch.contains("(40 requests/minute with 2 second minimum delay per host)"));
+}
+
+#[tokio::test]
+async fn test_run_http_transport() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}{}", listener.local_addr().unwrap(), MCP_PATH);
+    tokio::spawn(std::sync::Arc::new(McpServer::new()).run_http(listener));
+    let client = reqwest::Client::new();
+
+    let response = client
+        .post(&url)
+        .body(r#"{"jsonrpc":"2.0","id":7,"method":"tools/list"}"#)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.headers()["content-type"], "application/json");
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["id"], 7);
+    assert_eq!(body["result"]["tools"].as_array().unwrap().len(), 4);
+
+    // Notifications are acknowledged without a body
+    let response = client
+        .post(&url)
+        .body(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 202);
+
+    let body: serde_json::Value = client.post(&url).body("not json").send().await.unwrap().json().await.unwrap();
+    assert_eq!(body["error"]["code"], -32700);
+
+    assert_eq!(client.get(&url).send().await.unwrap().status(), 405);
+    let foreign = client.post(&url).header("Origin", "https://evil.example").body("{}").send().await.unwrap();
+    assert_eq!(foreign.status(), 403);
+    let local = client.post(&url).header("Origin", "http://localhost:3000").body("{}").send().await.unwrap();
+    assert_eq!(local.status(), 200);
 }
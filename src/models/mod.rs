@@ -1,6 +1,7 @@
 //! Data models for the MCP Web Search Server
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 // ============== MCP Protocol Types ==============
 
@@ -44,6 +45,7 @@ impl JsonRpcError {
 
 /// Tool definition for MCP
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Tool {
     pub name: String,
     pub description: String,
@@ -60,10 +62,14 @@ pub struct ToolContent {
 
 /// Tool response
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ToolResponse {
     pub content: Vec<ToolContent>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub is_error: Option<bool>,
+    /// Machine-readable result alongside the text content
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub structured_content: Option<serde_json::Value>,
 }
 
 // ============== Search Types ==============
@@ -78,12 +84,18 @@ pub struct SearchResult {
 
 /// Search response with pagination info
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct SearchResponse {
     pub query: String,
     pub results: Vec<SearchResult>,
     pub total_results: usize,
     pub returned: usize,
     pub offset: usize,
+    /// Results hidden because the domain policy blocks their site
+    #[serde(default)]
+    pub filtered: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryInfo>,
 }
 
 /// Search parameters
@@ -92,6 +104,8 @@ pub struct SearchParams {
     pub query: String,
     pub limit: usize,
     pub offset: usize,
+    /// Bypass cached results (fresh results are still cached)
+    pub no_cache: bool,
 }
 
 impl SearchParams {
@@ -100,8 +114,448 @@ impl SearchParams {
             query: query.into(),
             limit: limit.clamp(1, 9999),
             offset,
+            no_cache: false,
         }
     }
+
+    pub fn with_no_cache(mut self, no_cache: bool) -> Self {
+        self.no_cache = no_cache;
+        self
+    }
+
+    /// Cache key: the query with case and whitespace normalized.
+    ///
+    /// Limit and offset are left out because one upstream page is fetched
+    /// and paginated locally, so every window is served from the same entry.
+    pub fn cache_key(&self) -> String {
+        self.query
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase()
+    }
+}
+
+// ============== Fetch Types ==============
+
+/// How fetched page content is rendered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentFormat {
+    /// Main content as Markdown: headings, lists, code, tables and links kept
+    #[default]
+    Markdown,
+    /// Main content as plain text
+    Text,
+    /// The page's HTML, unprocessed
+    Html,
+}
+
+impl ContentFormat {
+    /// Parse a tool argument ("markdown", "text", "html" and common aliases)
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "markdown" | "md" => Some(Self::Markdown),
+            "text" | "plain" | "plaintext" | "plain_text" => Some(Self::Text),
+            "html" | "raw" | "raw_html" => Some(Self::Html),
+            _ => None,
+        }
+    }
+}
+
+/// Rough characters-per-token ratio used when lengths are given in tokens
+pub const CHARS_PER_TOKEN: usize = 4;
+
+/// Unit of `FetchOptions::max_length`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LengthUnit {
+    /// Characters
+    #[default]
+    Chars,
+    /// Approximate tokens (`CHARS_PER_TOKEN` characters each)
+    Tokens,
+}
+
+impl LengthUnit {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "chars" | "characters" => Some(Self::Chars),
+            "tokens" => Some(Self::Tokens),
+            _ => None,
+        }
+    }
+
+    /// Length in characters
+    pub fn to_chars(self, length: usize) -> usize {
+        match self {
+            Self::Chars => length,
+            Self::Tokens => length.saturating_mul(CHARS_PER_TOKEN),
+        }
+    }
+}
+
+/// Options for fetching a single page
+#[derive(Debug, Clone, Default)]
+pub struct FetchOptions {
+    /// Bypass cached content (fresh content is still cached)
+    pub no_cache: bool,
+    pub format: ContentFormat,
+    /// Character offset into the rendered content to start reading at
+    pub start_index: usize,
+    /// Longest window to return (default: `FetchOptions::DEFAULT_MAX_LENGTH` characters)
+    pub max_length: Option<usize>,
+    pub length_unit: LengthUnit,
+    /// Pages to read from paged documents such as PDFs (default: all)
+    pub pages: Option<PageRanges>,
+    /// JSONPath expression selecting part of a JSON document (default: the whole document)
+    pub json_path: Option<String>,
+}
+
+impl FetchOptions {
+    pub const DEFAULT_MAX_LENGTH: usize = 10_000;
+
+    /// Window size in characters
+    pub fn max_chars(&self) -> usize {
+        self.max_chars_or(Self::DEFAULT_MAX_LENGTH)
+    }
+
+    /// Window size in characters, with `default` characters when no length was given
+    pub fn max_chars_or(&self, default: usize) -> usize {
+        self.max_length
+            .map(|n| self.length_unit.to_chars(n))
+            .unwrap_or(default)
+            .max(1)
+    }
+}
+
+/// 1-based page selection such as "1-3,5,8-"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageRanges(Vec<(usize, Option<usize>)>);
+
+impl PageRanges {
+    /// Parse comma-separated pages and inclusive ranges; an open end runs to the last page
+    pub fn parse(value: &str) -> Option<Self> {
+        let ranges = value
+            .split(',')
+            .map(|part| {
+                let part = part.trim();
+                let (start, end) = match part.split_once('-') {
+                    Some((start, "")) => (start.trim().parse().ok()?, None),
+                    Some((start, end)) => (start.trim().parse().ok()?, Some(end.trim().parse().ok()?)),
+                    None => {
+                        let page = part.parse().ok()?;
+                        (page, Some(page))
+                    }
+                };
+                (start >= 1 && end.is_none_or(|end| end >= start)).then_some((start, end))
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        (!ranges.is_empty()).then_some(Self(ranges))
+    }
+
+    pub fn contains(&self, page: usize) -> bool {
+        self.0
+            .iter()
+            .any(|&(start, end)| page >= start && end.is_none_or(|end| page <= end))
+    }
+
+    /// First page requested
+    pub fn first(&self) -> usize {
+        self.0.iter().map(|&(start, _)| start).min().unwrap_or(1)
+    }
+}
+
+impl std::fmt::Display for PageRanges {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parts: Vec<String> = self
+            .0
+            .iter()
+            .map(|&(start, end)| match end {
+                Some(end) if end == start => start.to_string(),
+                Some(end) => format!("{}-{}", start, end),
+                None => format!("{}-", start),
+            })
+            .collect();
+        write!(f, "{}", parts.join(","))
+    }
+}
+
+/// Position of a returned window within the full rendered content
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContentChunk {
+    /// First character returned
+    pub start_index: usize,
+    /// One past the last character returned
+    pub end_index: usize,
+    /// Length of the full content, in characters
+    pub total_length: usize,
+    /// Where the next window starts; None once the end is reached
+    pub next_start_index: Option<usize>,
+}
+
+impl ContentChunk {
+    pub fn is_complete(&self) -> bool {
+        self.start_index == 0 && self.next_start_index.is_none()
+    }
+}
+
+/// Descriptive metadata of a fetched page, for citing sources
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PageMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Absolute canonical URL declared by the page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub canonical_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// Publication date as declared by the page (usually ISO 8601)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified: Option<String>,
+    /// Language tag, e.g. "en" or "ko-KR"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub site_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    /// Number of pages, for paged documents such as PDFs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_count: Option<usize>,
+    /// `og:*` properties, keyed without the prefix
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub open_graph: BTreeMap<String, String>,
+    /// `twitter:*` card properties, keyed without the prefix
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub twitter: BTreeMap<String, String>,
+    /// Parsed JSON-LD blocks
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub json_ld: Vec<serde_json::Value>,
+}
+
+impl PageMetadata {
+    /// Citation header for the text output, or None when nothing is known
+    pub fn header(&self) -> Option<String> {
+        let fields = [
+            ("Title", &self.title),
+            ("URL", &self.canonical_url),
+            ("Site", &self.site_name),
+            ("Author", &self.author),
+            ("Published", &self.published),
+            ("Modified", &self.modified),
+            ("Language", &self.language),
+            ("Description", &self.description),
+        ];
+        let mut lines: Vec<String> = fields
+            .iter()
+            .filter_map(|(label, value)| value.as_ref().map(|v| format!("{}: {}", label, v)))
+            .collect();
+        if let Some(count) = self.page_count {
+            lines.push(format!("Pages: {}", count));
+        }
+
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+}
+
+/// One redirect followed while fetching a page
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Redirect {
+    /// Status of the redirect response (301, 302, 303, 307 or 308)
+    pub status: u16,
+    /// Where it pointed
+    pub url: String,
+}
+
+/// A fetched page: the text shown to the model plus structured details
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FetchResponse {
+    /// URL that was requested
+    pub url: String,
+    /// URL the content came from, when redirects led elsewhere
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub final_url: Option<String>,
+    /// Redirects followed from `url` to `final_url`, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redirects: Vec<Redirect>,
+    /// Formatted output, sent as text content rather than repeated here
+    #[serde(skip)]
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<PageMetadata>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk: Option<ContentChunk>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryInfo>,
+}
+
+// ============== Link Types ==============
+
+/// Which links `extract_links` returns
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkScope {
+    #[default]
+    All,
+    /// Links to the page's own site
+    Internal,
+    /// Links to other sites
+    External,
+}
+
+impl LinkScope {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "all" => Some(Self::All),
+            "internal" | "same_domain" | "same-domain" => Some(Self::Internal),
+            "external" => Some(Self::External),
+            _ => None,
+        }
+    }
+}
+
+/// Options for extracting the links of a page
+#[derive(Debug, Clone)]
+pub struct LinkOptions {
+    pub scope: LinkScope,
+    /// Keep only URLs containing this text; `*` matches any run of characters
+    pub pattern: Option<String>,
+    /// Most links returned
+    pub limit: usize,
+    /// Bypass cached content (fresh content is still cached)
+    pub no_cache: bool,
+}
+
+impl Default for LinkOptions {
+    fn default() -> Self {
+        Self {
+            scope: LinkScope::All,
+            pattern: None,
+            limit: 100,
+            no_cache: false,
+        }
+    }
+}
+
+/// A hyperlink found on a page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageLink {
+    /// Absolute URL, resolved against the page (and its `<base href>`)
+    pub url: String,
+    /// Anchor text, or the image alt/title text for image links
+    pub text: String,
+    /// `rel` attribute values, lowercased (e.g. "nofollow", "sponsored")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rel: Vec<String>,
+    /// Whether the link stays on the page's site
+    pub internal: bool,
+}
+
+/// Links extracted from one page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinksResponse {
+    pub url: String,
+    pub links: Vec<PageLink>,
+    /// Distinct links on the page before filtering
+    pub total: usize,
+    /// Links removed by the scope and pattern filters
+    pub filtered: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryInfo>,
+}
+
+// ============== Selector Types ==============
+
+/// Options for extracting elements matched by CSS selectors
+#[derive(Debug, Clone)]
+pub struct SelectorOptions {
+    /// CSS selectors, each reported separately
+    pub selectors: Vec<String>,
+    pub format: ContentFormat,
+    /// Most matches rendered per selector
+    pub limit: usize,
+    /// Longest output, in characters, across all matches
+    pub max_length: usize,
+    /// Bypass cached content (fresh content is still cached)
+    pub no_cache: bool,
+}
+
+impl Default for SelectorOptions {
+    fn default() -> Self {
+        Self {
+            selectors: Vec::new(),
+            format: ContentFormat::default(),
+            limit: 10,
+            max_length: FetchOptions::DEFAULT_MAX_LENGTH,
+            no_cache: false,
+        }
+    }
+}
+
+/// Elements matched by one selector
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelectorMatches {
+    pub selector: String,
+    /// Elements matching on the page, including ones past the limit
+    pub total: usize,
+    /// Rendered matches, in document order
+    pub matches: Vec<String>,
+}
+
+/// Result of `fetch_selector` for one page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelectorResponse {
+    pub url: String,
+    pub results: Vec<SelectorMatches>,
+    /// Whether rendered matches were cut to fit `max_length`
+    pub truncated: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryInfo>,
+}
+
+// ============== Retry Types ==============
+
+/// Summary of the attempts made for a single upstream request
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetryInfo {
+    pub attempts: u32,
+    /// Why each failed attempt was retried (e.g. "HTTP 503", "timeout")
+    pub reasons: Vec<String>,
+    /// Whether a retried attempt was answered with HTTP 429
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub throttled: bool,
+}
+
+impl RetryInfo {
+    /// Whether at least one retry happened
+    pub fn retried(&self) -> bool {
+        self.attempts > 1
+    }
+
+    /// Fold in the retries of a follow-up request, e.g. the next redirect hop
+    pub fn merge(&mut self, later: RetryInfo) {
+        self.attempts += later.attempts.saturating_sub(1);
+        self.reasons.extend(later.reasons);
+        self.throttled |= later.throttled;
+    }
+
+    /// Short note appended to tool output when retries occurred
+    pub fn note(&self) -> Option<String> {
+        if !self.retried() {
+            return None;
+        }
+        Some(format!(
+            "[Completed after {} attempts; retried on: {}]",
+            self.attempts,
+            self.reasons.join(", ")
+        ))
+    }
 }
 
 #[cfg(test)]
@@ -1,129 +1,252 @@
 //! Unit tests for models
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use serde_json::json;
-
-    #[test]
-    fn test_search_params_clamping() {
-        // Test lower bound
-        let params = SearchParams::new("test", 0, 0);
-        assert_eq!(params.limit, 1); // Should clamp to minimum
-
-        // Test upper bound
-        let params = SearchParams::new("test", 10000, 0);
-        assert_eq!(params.limit, 9999); // Should clamp to maximum
-
-        // Test normal values
-        let params = SearchParams::new("test", 10, 5);
-        assert_eq!(params.limit, 10);
-        assert_eq!(params.offset, 5);
-        assert_eq!(params.query, "test");
-    }
+use super::*;
+use serde_json::json;
+
+#[test]
+fn test_search_params_clamping() {
+    // Test lower bound
+    let params = SearchParams::new("test", 0, 0);
+    assert_eq!(params.limit, 1); // Should clamp to minimum
+
+    // Test upper bound
+    let params = SearchParams::new("test", 10000, 0);
+    assert_eq!(params.limit, 9999); // Should clamp to maximum
+
+    // Test normal values
+    let params = SearchParams::new("test", 10, 5);
+    assert_eq!(params.limit, 10);
+    assert_eq!(params.offset, 5);
+    assert_eq!(params.query, "test");
+}
 
-    #[test]
-    fn test_json_rpc_error_creation() {
-        let error = JsonRpcError::new(-32600, "Test error");
-        assert_eq!(error.code, -32600);
-        assert_eq!(error.message, "Test error");
-    }
+#[test]
+fn test_search_params_cache_key_normalization() {
+    let a = SearchParams::new("  Rust   Programming ", 5, 0);
+    let b = SearchParams::new("rust programming", 10, 20).with_no_cache(true);
+
+    assert_eq!(a.cache_key(), "rust programming");
+    assert_eq!(a.cache_key(), b.cache_key());
+    assert!(!a.no_cache);
+    assert!(b.no_cache);
+}
+
+#[test]
+fn test_json_rpc_error_creation() {
+    let error = JsonRpcError::new(-32600, "Test error");
+    assert_eq!(error.code, -32600);
+    assert_eq!(error.message, "Test error");
+}
+
+#[test]
+fn test_search_result_serialization() {
+    let result = SearchResult {
+        title: "Test Title".to_string(),
+        url: "https://example.com".to_string(),
+        snippet: "Test snippet".to_string(),
+    };
 
-    #[test]
-    fn test_search_result_serialization() {
-        let result = SearchResult {
-            title: "Test Title".to_string(),
+    let json = serde_json::to_string(&result).unwrap();
+    let parsed: SearchResult = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(parsed.title, "Test Title");
+    assert_eq!(parsed.url, "https://example.com");
+    assert_eq!(parsed.snippet, "Test snippet");
+}
+
+#[test]
+fn test_search_response_serialization() {
+    let response = SearchResponse {
+        query: "test query".to_string(),
+        results: vec![SearchResult {
+            title: "Test".to_string(),
             url: "https://example.com".to_string(),
-            snippet: "Test snippet".to_string(),
-        };
+            snippet: "Snippet".to_string(),
+        }],
+        total_results: 1,
+        returned: 1,
+        offset: 0,
+        filtered: 2,
+        retry: None,
+    };
+
+    let json = serde_json::to_string(&response).unwrap();
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(value["query"], "test query");
+    assert_eq!(value["filtered"], 2);
+    assert_eq!(value["totalResults"], 1);
+    assert_eq!(value["results"].as_array().unwrap().len(), 1);
+    assert!(value.get("retry").is_none());
+}
 
-        let json = serde_json::to_string(&result).unwrap();
-        let parsed: SearchResult = serde_json::from_str(&json).unwrap();
+#[test]
+fn test_retry_info_note() {
+    let info = RetryInfo {
+        attempts: 1,
+        ..Default::default()
+    };
+    assert!(!info.retried());
+    assert!(info.note().is_none());
+
+    let info = RetryInfo {
+        attempts: 3,
+        reasons: vec!["HTTP 503".to_string(), "timeout".to_string()],
+        ..Default::default()
+    };
+    assert!(info.retried());
+    assert_eq!(
+        info.note().unwrap(),
+        "[Completed after 3 attempts; retried on: HTTP 503, timeout]"
+    );
+}
 
-        assert_eq!(parsed.title, "Test Title");
-        assert_eq!(parsed.url, "https://example.com");
-        assert_eq!(parsed.snippet, "Test snippet");
-    }
+#[test]
+fn test_json_rpc_request_deserialization() {
+    let json = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "tools/list",
+        "params": null
+    });
+
+    let request: JsonRpcRequest = serde_json::from_value(json).unwrap();
+    assert_eq!(request.jsonrpc, "2.0");
+    assert_eq!(request.method, "tools/list");
+    assert_eq!(request.id, Some(serde_json::json!(1)));
+}
 
-    #[test]
-    fn test_search_response_serialization() {
-        let response = SearchResponse {
-            query: "test query".to_string(),
-            results: vec![SearchResult {
-                title: "Test".to_string(),
-                url: "https://example.com".to_string(),
-                snippet: "Snippet".to_string(),
-            }],
-            total_results: 1,
-            returned: 1,
-            offset: 0,
-        };
-
-        let json = serde_json::to_string(&response).unwrap();
-        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
-
-        assert_eq!(value["query"], "test query");
-        assert_eq!(value["totalResults"], 1);
-        assert_eq!(value["results"].as_array().unwrap().len(), 1);
-    }
+#[test]
+fn test_json_rpc_response_serialization() {
+    let response = JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id: Some(serde_json::json!(1)),
+        result: Some(json!({"status": "ok"})),
+        error: None,
+    };
+
+    let json = serde_json::to_string(&response).unwrap();
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(value["jsonrpc"], "2.0");
+    assert_eq!(value["id"], 1);
+    assert_eq!(value["result"]["status"], "ok");
+    assert!(value["error"].is_null());
+}
 
-    #[test]
-    fn test_json_rpc_request_deserialization() {
-        let json = json!({
-            "jsonrpc": "2.0",
-            "id": 1,
-            "method": "tools/list",
-            "params": null
-        });
-
-        let request: JsonRpcRequest = serde_json::from_value(json).unwrap();
-        assert_eq!(request.jsonrpc, "2.0");
-        assert_eq!(request.method, "tools/list");
-        assert_eq!(request.id, Some(serde_json::json!(1)));
-    }
+#[test]
+fn test_tool_uses_mcp_field_names() {
+    // MCP clients look for `inputSchema`; snake_case would hide every argument
+    let tool = Tool {
+        name: "web_search".to_string(),
+        description: "Search".to_string(),
+        input_schema: json!({"type": "object"}),
+    };
 
-    #[test]
-    fn test_json_rpc_response_serialization() {
-        let response = JsonRpcResponse {
-            jsonrpc: "2.0".to_string(),
-            id: Some(serde_json::json!(1)),
-            result: Some(json!({"status": "ok"})),
-            error: None,
-        };
-
-        let json = serde_json::to_string(&response).unwrap();
-        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
-
-        assert_eq!(value["jsonrpc"], "2.0");
-        assert_eq!(value["id"], 1);
-        assert_eq!(value["result"]["status"], "ok");
-        assert!(value["error"].is_null());
-    }
+    let value = serde_json::to_value(&tool).unwrap();
+
+    assert_eq!(value["inputSchema"]["type"], "object");
+    assert!(value.get("input_schema").is_none());
+}
+
+#[test]
+fn test_tool_response_is_error_flag() {
+    let success_response = ToolResponse {
+        content: vec![ToolContent {
+            content_type: "text".to_string(),
+            text: "Success".to_string(),
+        }],
+        is_error: None,
+        structured_content: None,
+    };
+
+    let error_response = ToolResponse {
+        content: vec![ToolContent {
+            content_type: "text".to_string(),
+            text: "Error".to_string(),
+        }],
+        is_error: Some(true),
+        structured_content: None,
+    };
+
+    // Serialize and check that is_error is omitted when None
+    let json_success = serde_json::to_string(&success_response).unwrap();
+    assert!(!json_success.contains("isError"));
+
+    // And included when Some(true)
+    let json_error = serde_json::to_string(&error_response).unwrap();
+    assert!(json_error.contains("\"isError\":true"));
+}
+
+#[test]
+fn test_content_format_parse() {
+    assert_eq!(ContentFormat::parse("markdown"), Some(ContentFormat::Markdown));
+    assert_eq!(ContentFormat::parse(" MD "), Some(ContentFormat::Markdown));
+    assert_eq!(ContentFormat::parse("plain_text"), Some(ContentFormat::Text));
+    assert_eq!(ContentFormat::parse("raw_html"), Some(ContentFormat::Html));
+    assert_eq!(ContentFormat::parse("pdf"), None);
+    assert_eq!(ContentFormat::default(), ContentFormat::Markdown);
+}
+
+#[test]
+fn test_fetch_options_max_chars() {
+    assert_eq!(FetchOptions::default().max_chars(), FetchOptions::DEFAULT_MAX_LENGTH);
+
+    let tokens = FetchOptions {
+        max_length: Some(500),
+        length_unit: LengthUnit::Tokens,
+        ..Default::default()
+    };
+    assert_eq!(tokens.max_chars(), 500 * CHARS_PER_TOKEN);
+
+    // The default is already in characters, whatever the unit
+    let tokens_default = FetchOptions {
+        length_unit: LengthUnit::Tokens,
+        ..Default::default()
+    };
+    assert_eq!(tokens_default.max_chars(), FetchOptions::DEFAULT_MAX_LENGTH);
+    assert_eq!(tokens_default.max_chars_or(2500), 2500);
+
+    let zero = FetchOptions {
+        max_length: Some(0),
+        ..Default::default()
+    };
+    assert_eq!(zero.max_chars(), 1);
+    assert_eq!(LengthUnit::parse("Characters"), Some(LengthUnit::Chars));
+}
+
+#[test]
+fn test_page_metadata_header() {
+    assert_eq!(PageMetadata::default().header(), None);
+
+    let metadata = PageMetadata {
+        title: Some("Guide".to_string()),
+        canonical_url: Some("https://example.com/guide".to_string()),
+        published: Some("2024-01-02".to_string()),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        metadata.header().unwrap(),
+        "Title: Guide\nURL: https://example.com/guide\nPublished: 2024-01-02"
+    );
+    // Empty collections are left out of structured output
+    let json = serde_json::to_value(&metadata).unwrap();
+    assert!(json.get("open_graph").is_none());
+}
+
+#[test]
+fn test_page_ranges_parse() {
+    let ranges = PageRanges::parse("1-3, 5,8-").unwrap();
+
+    assert!(ranges.contains(2));
+    assert!(!ranges.contains(4));
+    assert!(ranges.contains(5));
+    assert!(ranges.contains(80));
+    assert_eq!(ranges.first(), 1);
+    assert_eq!(ranges.to_string(), "1-3,5,8-");
 
-    #[test]
-    fn test_tool_response_is_error_flag() {
-        let success_response = ToolResponse {
-            content: vec![ToolContent {
-                content_type: "text".to_string(),
-                text: "Success".to_string(),
-            }],
-            is_error: None,
-        };
-
-        let error_response = ToolResponse {
-            content: vec![ToolContent {
-                content_type: "text".to_string(),
-                text: "Error".to_string(),
-            }],
-            is_error: Some(true),
-        };
-
-        // Serialize and check that is_error is omitted when None
-        let json_success = serde_json::to_string(&success_response).unwrap();
-        assert!(!json_success.contains("isError"));
-
-        // And included when Some(true)
-        let json_error = serde_json::to_string(&error_response).unwrap();
-        assert!(json_error.contains("\"isError\":true"));
+    for invalid in ["", "0", "3-1", "a-b", "1,,2", "-4"] {
+        assert_eq!(PageRanges::parse(invalid), None, "{invalid:?}");
     }
 }
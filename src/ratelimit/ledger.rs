@@ -0,0 +1,131 @@
+//! On-disk request ledger shared by every local server process
+//!
+//! Clients typically spawn one server process per session, so an in-memory
+//! limiter alone lets several agents exceed the search budget together.
+//! The ledger records request timestamps in a small file guarded by an
+//! exclusive file lock; each process consults it before sending.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// File-locked sliding-window ledger, keyed by budget name (e.g. "search")
+#[derive(Debug, Clone)]
+pub struct RateLedger {
+    path: PathBuf,
+}
+
+impl RateLedger {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Wait until the machine-wide budget allows a request, then record it
+    pub async fn reserve(&self, key: &str, max_per_minute: usize, min_delay: Duration) -> io::Result<()> {
+        loop {
+            let ledger = self.clone();
+            let key = key.to_string();
+            let wait = tokio::task::spawn_blocking(move || {
+                ledger.try_reserve(&key, max_per_minute, min_delay, SystemTime::now())
+            })
+            .await
+            .map_err(io::Error::other)??;
+
+            match wait {
+                None => return Ok(()),
+                Some(wait) => tokio::time::sleep(wait).await,
+            }
+        }
+    }
+
+    /// Record a request at `now` if allowed; otherwise return how long to wait
+    pub fn try_reserve(
+        &self,
+        key: &str,
+        max_per_minute: usize,
+        min_delay: Duration,
+        now: SystemTime,
+    ) -> io::Result<Option<Duration>> {
+        let now_ms = millis(now);
+        let window_ms = WINDOW.as_millis() as u64;
+
+        let mut file = self.open_locked()?;
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)?;
+
+        // Drop entries outside the window (for every key) and malformed lines
+        let mut entries: Vec<(String, u64)> = contents
+            .lines()
+            .filter_map(|line| {
+                let (name, ts) = line.split_once(' ')?;
+                Some((name.to_string(), ts.trim().parse().ok()?))
+            })
+            .filter(|(_, ts)| now_ms.saturating_sub(*ts) < window_ms)
+            .collect();
+
+        let mut ours: Vec<u64> = entries
+            .iter()
+            .filter(|(name, _)| name == key)
+            .map(|(_, ts)| *ts)
+            .collect();
+        ours.sort_unstable();
+
+        let delay_wait = ours
+            .last()
+            .map(|last| (last + min_delay.as_millis() as u64).saturating_sub(now_ms))
+            .unwrap_or(0);
+        let limit = max_per_minute.max(1);
+        let window_wait = if ours.len() >= limit {
+            (ours[ours.len() - limit] + window_ms).saturating_sub(now_ms)
+        } else {
+            0
+        };
+
+        let wait = delay_wait.max(window_wait);
+        if wait > 0 {
+            return Ok(Some(Duration::from_millis(wait)));
+        }
+
+        entries.push((key.to_string(), now_ms));
+        let mut out = String::new();
+        for (name, ts) in &entries {
+            out.push_str(&format!("{} {}\n", name, ts));
+        }
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(out.as_bytes())?;
+        file.flush()?;
+
+        // The lock is released when `file` is dropped
+        Ok(None)
+    }
+
+    fn open_locked(&self) -> io::Result<File> {
+        if let Some(dir) = self.path.parent()
+            && !dir.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&self.path)?;
+        file.lock()?;
+        Ok(file)
+    }
+}
+
+fn millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_millis() as u64
+}
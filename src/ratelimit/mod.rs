@@ -0,0 +1,336 @@
+//! Adaptive token-bucket rate limiting for outgoing requests
+
+mod ledger;
+
+pub use ledger::RateLedger;
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::time::Instant;
+
+/// Idle per-host limiters are dropped once this many hosts are tracked
+const MAX_TRACKED_HOSTS: usize = 256;
+
+/// Tolerance for floating-point refill so a sleep never ends just short of a token
+const TOKEN_EPSILON: f64 = 1e-9;
+
+/// Budget for one class of requests
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    /// Sustained rate the bucket refills at
+    pub max_requests_per_minute: usize,
+    /// Bucket capacity: requests allowed back-to-back after an idle period
+    pub burst: usize,
+    /// Minimum spacing between two requests, regardless of available tokens
+    pub min_delay: Duration,
+}
+
+impl RateLimitConfig {
+    pub fn new(max_requests_per_minute: usize, min_delay: Duration) -> Self {
+        Self {
+            max_requests_per_minute,
+            burst: 1,
+            min_delay,
+        }
+    }
+
+    pub fn with_burst(mut self, burst: usize) -> Self {
+        self.burst = burst;
+        self
+    }
+}
+
+/// How the limiter reacts to throttling signals (CAPTCHA pages, HTTP 429)
+#[derive(Debug, Clone)]
+pub struct AdaptiveConfig {
+    /// Slowdown multiplier applied on every throttling signal
+    pub backoff_factor: f64,
+    /// Multiplier (below 1.0) applied to the slowdown after every success
+    pub recovery_factor: f64,
+    /// Largest slowdown relative to the configured rate
+    pub max_slowdown: f64,
+}
+
+impl Default for AdaptiveConfig {
+    fn default() -> Self {
+        Self {
+            backoff_factor: 2.0,
+            recovery_factor: 0.9,
+            max_slowdown: 16.0,
+        }
+    }
+}
+
+/// Token-bucket rate limiter to avoid getting blocked.
+///
+/// Callers are served strictly in arrival order: only the head of the
+/// waiter queue may take a token, so concurrent callers can never wake
+/// together and exceed the rate. The refill rate is divided (and the
+/// minimum delay multiplied) by a slowdown factor that grows when the
+/// upstream throttles us and decays back to 1.0 as requests succeed.
+pub struct RateLimiter {
+    state: Mutex<State>,
+    notify: Notify,
+    max_requests_per_minute: usize,
+    burst: usize,
+    min_delay: Duration,
+    adaptive: AdaptiveConfig,
+    ledger: Option<(RateLedger, String)>,
+}
+
+struct State {
+    tokens: f64,
+    last_refill: Instant,
+    next_allowed: Option<Instant>,
+    queue: VecDeque<u64>,
+    next_ticket: u64,
+    slowdown: f64,
+}
+
+/// Removes an abandoned ticket so a cancelled `acquire` never blocks the queue
+struct Ticket<'a> {
+    limiter: &'a RateLimiter,
+    id: u64,
+    served: bool,
+}
+
+impl Drop for Ticket<'_> {
+    fn drop(&mut self) {
+        if self.served {
+            return;
+        }
+        let mut state = self.limiter.state.lock().unwrap();
+        let was_head = state.queue.front() == Some(&self.id);
+        state.queue.retain(|id| *id != self.id);
+        drop(state);
+        if was_head {
+            self.limiter.notify.notify_waiters();
+        }
+    }
+}
+
+impl RateLimiter {
+    pub fn new(max_requests_per_minute: usize, min_delay_secs: u64) -> Self {
+        Self::with_config(&RateLimitConfig::new(
+            max_requests_per_minute,
+            Duration::from_secs(min_delay_secs),
+        ))
+    }
+
+    pub fn with_config(config: &RateLimitConfig) -> Self {
+        let burst = config.burst.max(1);
+        Self {
+            state: Mutex::new(State {
+                tokens: burst as f64,
+                last_refill: Instant::now(),
+                next_allowed: None,
+                queue: VecDeque::new(),
+                next_ticket: 0,
+                slowdown: 1.0,
+            }),
+            notify: Notify::new(),
+            max_requests_per_minute: config.max_requests_per_minute.max(1),
+            burst,
+            min_delay: config.min_delay,
+            adaptive: AdaptiveConfig::default(),
+            ledger: None,
+        }
+    }
+
+    pub fn with_adaptive(mut self, adaptive: AdaptiveConfig) -> Self {
+        self.adaptive = adaptive;
+        self
+    }
+
+    /// Also enforce this budget machine-wide through a shared on-disk ledger
+    pub fn with_ledger(mut self, ledger: RateLedger, key: impl Into<String>) -> Self {
+        self.ledger = Some((ledger, key.into()));
+        self
+    }
+
+    /// Wait in line until a token is available under the current effective rate
+    pub async fn acquire(&self) {
+        self.acquire_local().await;
+
+        if let Some((ledger, key)) = &self.ledger {
+            let limit = (self.effective_rate().floor() as usize).max(1);
+            if let Err(e) = ledger.reserve(key, limit, self.effective_min_delay()).await {
+                // The ledger is best effort: never block requests on a broken file
+                eprintln!("Rate-limit ledger {} unavailable: {}", ledger.path().display(), e);
+            }
+        }
+    }
+
+    async fn acquire_local(&self) {
+        let mut ticket = {
+            let mut state = self.state.lock().unwrap();
+            let id = state.next_ticket;
+            state.next_ticket += 1;
+            state.queue.push_back(id);
+            Ticket {
+                limiter: self,
+                id,
+                served: false,
+            }
+        };
+
+        loop {
+            // Register for wake-ups before inspecting the queue so a
+            // notification sent in between is not lost
+            let notified = self.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            let wait = {
+                let mut state = self.state.lock().unwrap();
+                let now = Instant::now();
+                self.refill(&mut state, now);
+
+                if state.queue.front() != Some(&ticket.id) {
+                    None
+                } else {
+                    let token_wait = if state.tokens + TOKEN_EPSILON >= 1.0 {
+                        Duration::ZERO
+                    } else {
+                        Duration::from_secs_f64(
+                            (1.0 - state.tokens) / self.tokens_per_sec(state.slowdown),
+                        )
+                    };
+                    let delay_wait = state
+                        .next_allowed
+                        .map(|at| at.saturating_duration_since(now))
+                        .unwrap_or(Duration::ZERO);
+
+                    let wait = token_wait.max(delay_wait);
+                    if wait.is_zero() {
+                        state.tokens = (state.tokens - 1.0).max(0.0);
+                        state.next_allowed = Some(now + self.min_delay.mul_f64(state.slowdown));
+                        state.queue.pop_front();
+                        ticket.served = true;
+                        drop(state);
+                        // Let the next caller in line start its own wait
+                        self.notify.notify_waiters();
+                        return;
+                    }
+                    Some(wait)
+                }
+            };
+
+            // Sleep outside the lock, then re-check
+            match wait {
+                Some(wait) => tokio::time::sleep(wait).await,
+                None => notified.await,
+            }
+        }
+    }
+
+    /// Slow down after a CAPTCHA or HTTP 429
+    pub fn record_throttled(&self) {
+        let mut state = self.state.lock().unwrap();
+        self.refill(&mut state, Instant::now());
+        state.slowdown = (state.slowdown * self.adaptive.backoff_factor)
+            .clamp(1.0, self.adaptive.max_slowdown.max(1.0));
+    }
+
+    /// Recover gradually towards the configured rate
+    pub fn record_success(&self) {
+        let mut state = self.state.lock().unwrap();
+        self.refill(&mut state, Instant::now());
+        state.slowdown = (state.slowdown * self.adaptive.recovery_factor).max(1.0);
+    }
+
+    /// Current slowdown relative to the configured rate (1.0 = full speed)
+    pub fn slowdown(&self) -> f64 {
+        self.state.lock().unwrap().slowdown
+    }
+
+    /// Requests per minute currently allowed
+    pub fn effective_rate(&self) -> f64 {
+        self.max_requests_per_minute as f64 / self.slowdown()
+    }
+
+    /// Minimum delay between requests currently enforced
+    pub fn effective_min_delay(&self) -> Duration {
+        self.min_delay.mul_f64(self.slowdown())
+    }
+
+    /// Callers currently waiting for a token
+    pub fn waiting(&self) -> usize {
+        self.state.lock().unwrap().queue.len()
+    }
+
+    /// Bucket full, nobody waiting and no pending slowdown
+    fn is_idle(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        self.refill(&mut state, Instant::now());
+        state.slowdown <= 1.0
+            && state.queue.is_empty()
+            && state.tokens + TOKEN_EPSILON >= self.burst as f64
+    }
+
+    fn tokens_per_sec(&self, slowdown: f64) -> f64 {
+        self.max_requests_per_minute as f64 / 60.0 / slowdown
+    }
+
+    fn refill(&self, state: &mut State, now: Instant) {
+        let elapsed = now.saturating_duration_since(state.last_refill).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * self.tokens_per_sec(state.slowdown))
+            .min(self.burst as f64);
+        state.last_refill = now;
+    }
+}
+
+/// Separate budgets for the search engine and for each fetched website.
+///
+/// Searches share one limiter; page fetches get an independent, lazily
+/// created limiter per host so reading pages never starves searches and
+/// fetches to different hosts proceed in parallel.
+pub struct HostRateLimiter {
+    search: RateLimiter,
+    per_host: RateLimitConfig,
+    hosts: Mutex<HashMap<String, Arc<RateLimiter>>>,
+}
+
+impl HostRateLimiter {
+    pub fn new(search: RateLimitConfig, per_host: RateLimitConfig) -> Self {
+        Self::with_search_limiter(RateLimiter::with_config(&search), per_host)
+    }
+
+    pub fn with_search_limiter(search: RateLimiter, per_host: RateLimitConfig) -> Self {
+        Self {
+            search,
+            per_host,
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Limiter for search engine requests
+    pub fn search(&self) -> &RateLimiter {
+        &self.search
+    }
+
+    /// Limiter for requests to the given host (case-insensitive)
+    pub fn for_host(&self, host: &str) -> Arc<RateLimiter> {
+        let key = host.to_ascii_lowercase();
+        let mut hosts = self.hosts.lock().unwrap();
+
+        if hosts.len() >= MAX_TRACKED_HOSTS && !hosts.contains_key(&key) {
+            hosts.retain(|_, limiter| Arc::strong_count(limiter) > 1 || !limiter.is_idle());
+        }
+
+        hosts
+            .entry(key)
+            .or_insert_with(|| Arc::new(RateLimiter::with_config(&self.per_host)))
+            .clone()
+    }
+
+    /// Number of hosts with a live limiter
+    pub fn tracked_hosts(&self) -> usize {
+        self.hosts.lock().unwrap().len()
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,356 @@
+//! Unit tests for rate limiting
+
+use super::*;
+use std::sync::{Arc, Mutex};
+
+#[tokio::test(start_paused = true)]
+async fn test_min_delay_between_requests() {
+    let limiter = RateLimiter::new(20, 3);
+    let start = Instant::now();
+
+    limiter.acquire().await;
+    assert_eq!(start.elapsed(), Duration::ZERO);
+
+    limiter.acquire().await;
+    assert_eq!(start.elapsed(), Duration::from_secs(3));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_refill_rate() {
+    let limiter = RateLimiter::new(2, 0);
+    let start = Instant::now();
+
+    limiter.acquire().await;
+    assert_eq!(start.elapsed(), Duration::ZERO);
+
+    // One token every 30 seconds
+    limiter.acquire().await;
+    assert_eq!(start.elapsed(), Duration::from_secs(30));
+    limiter.acquire().await;
+    assert_eq!(start.elapsed(), Duration::from_secs(60));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_burst_capacity() {
+    let limiter = RateLimiter::with_config(&RateLimitConfig::new(60, Duration::ZERO).with_burst(3));
+    let start = Instant::now();
+
+    for _ in 0..3 {
+        limiter.acquire().await;
+    }
+    assert_eq!(start.elapsed(), Duration::ZERO);
+
+    limiter.acquire().await;
+    assert_eq!(start.elapsed(), Duration::from_secs(1));
+
+    // Idle time refills the bucket, but never beyond its capacity
+    tokio::time::sleep(Duration::from_secs(100)).await;
+    let resumed = Instant::now();
+    for _ in 0..3 {
+        limiter.acquire().await;
+    }
+    assert_eq!(resumed.elapsed(), Duration::ZERO);
+    limiter.acquire().await;
+    assert_eq!(resumed.elapsed(), Duration::from_secs(1));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_concurrent_callers_are_served_fifo_at_rate() {
+    let limiter = Arc::new(RateLimiter::new(60, 0));
+    let start = Instant::now();
+    let served = Arc::new(Mutex::new(Vec::new()));
+
+    let mut handles = Vec::new();
+    for i in 0..8 {
+        let limiter = limiter.clone();
+        let served = served.clone();
+        handles.push(tokio::spawn(async move {
+            limiter.acquire().await;
+            served.lock().unwrap().push((i, start.elapsed()));
+        }));
+        // Let the task enqueue before spawning the next one
+        tokio::task::yield_now().await;
+    }
+    for handle in handles {
+        handle.await.unwrap();
+    }
+
+    let served = served.lock().unwrap();
+    let expected: Vec<_> = (0..8).map(|i| (i, Duration::from_secs(i as u64))).collect();
+    assert_eq!(*served, expected);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_cancelled_waiter_gives_up_its_turn() {
+    let limiter = Arc::new(RateLimiter::new(60, 0));
+    limiter.acquire().await;
+    let start = Instant::now();
+
+    let first = tokio::spawn({
+        let limiter = limiter.clone();
+        async move { limiter.acquire().await }
+    });
+    tokio::task::yield_now().await;
+    let second = tokio::spawn({
+        let limiter = limiter.clone();
+        async move { limiter.acquire().await }
+    });
+    tokio::task::yield_now().await;
+    assert_eq!(limiter.waiting(), 2);
+
+    first.abort();
+    let _ = first.await;
+    second.await.unwrap();
+
+    // The second caller takes the token the first one abandoned
+    assert_eq!(start.elapsed(), Duration::from_secs(1));
+    assert_eq!(limiter.waiting(), 0);
+}
+
+#[test]
+fn test_throttling_backs_off_multiplicatively() {
+    let limiter = RateLimiter::new(20, 3);
+    assert_eq!(limiter.effective_rate(), 20.0);
+
+    limiter.record_throttled();
+    assert_eq!(limiter.slowdown(), 2.0);
+    assert_eq!(limiter.effective_rate(), 10.0);
+    assert_eq!(limiter.effective_min_delay(), Duration::from_secs(6));
+
+    limiter.record_throttled();
+    assert_eq!(limiter.effective_rate(), 5.0);
+
+    // Capped at max_slowdown
+    for _ in 0..10 {
+        limiter.record_throttled();
+    }
+    assert_eq!(limiter.slowdown(), 16.0);
+}
+
+#[test]
+fn test_success_recovers_gradually() {
+    let limiter = RateLimiter::new(20, 3).with_adaptive(AdaptiveConfig {
+        backoff_factor: 4.0,
+        recovery_factor: 0.5,
+        max_slowdown: 8.0,
+    });
+
+    limiter.record_throttled();
+    assert_eq!(limiter.slowdown(), 4.0);
+
+    limiter.record_success();
+    assert_eq!(limiter.slowdown(), 2.0);
+    limiter.record_success();
+    assert_eq!(limiter.slowdown(), 1.0);
+
+    // Never faster than configured
+    limiter.record_success();
+    assert_eq!(limiter.slowdown(), 1.0);
+    assert_eq!(limiter.effective_rate(), 20.0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_throttled_limiter_waits_longer() {
+    let limiter = RateLimiter::new(20, 3);
+    limiter.record_throttled();
+    let start = Instant::now();
+
+    limiter.acquire().await;
+    limiter.acquire().await;
+    assert_eq!(start.elapsed(), Duration::from_secs(6));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_throttled_limiter_lowers_refill_rate() {
+    let limiter = RateLimiter::new(4, 0);
+    limiter.record_throttled();
+    let start = Instant::now();
+
+    limiter.acquire().await;
+    assert_eq!(start.elapsed(), Duration::ZERO);
+
+    // 2 requests/minute while throttled
+    limiter.acquire().await;
+    assert_eq!(start.elapsed(), Duration::from_secs(30));
+}
+
+fn host_limiter() -> HostRateLimiter {
+    HostRateLimiter::new(
+        RateLimitConfig::new(20, Duration::from_secs(3)),
+        RateLimitConfig::new(10, Duration::from_secs(1)).with_burst(5),
+    )
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_different_hosts_do_not_wait_for_each_other() {
+    let limits = host_limiter();
+    let start = Instant::now();
+
+    limits.for_host("example.com").acquire().await;
+    limits.for_host("rust-lang.org").acquire().await;
+    limits.for_host("docs.rs").acquire().await;
+    assert_eq!(start.elapsed(), Duration::ZERO);
+
+    // Same host (case-insensitive) waits for its own min delay
+    limits.for_host("Example.COM").acquire().await;
+    assert_eq!(start.elapsed(), Duration::from_secs(1));
+    assert_eq!(limits.tracked_hosts(), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_search_budget_is_independent_of_fetches() {
+    let limits = host_limiter();
+    let start = Instant::now();
+
+    limits.search().acquire().await;
+    for _ in 0..5 {
+        limits.for_host("example.com").acquire().await;
+    }
+    assert_eq!(start.elapsed(), Duration::from_secs(4));
+
+    // The search limiter only counts its own request from t=0
+    limits.search().acquire().await;
+    assert_eq!(start.elapsed(), Duration::from_secs(4));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_parallel_fetches_to_different_hosts() {
+    let limits = Arc::new(host_limiter());
+    // Prime both hosts so the next acquire has to wait 1s
+    limits.for_host("a.example").acquire().await;
+    limits.for_host("b.example").acquire().await;
+    let start = Instant::now();
+
+    let a = limits.for_host("a.example");
+    let b = limits.for_host("b.example");
+    tokio::join!(a.acquire(), b.acquire());
+
+    assert_eq!(start.elapsed(), Duration::from_secs(1));
+}
+
+#[test]
+fn test_host_throttling_is_isolated() {
+    let limits = host_limiter();
+
+    limits.for_host("slow.example").record_throttled();
+
+    assert_eq!(limits.for_host("slow.example").effective_rate(), 5.0);
+    assert_eq!(limits.for_host("fast.example").effective_rate(), 10.0);
+    assert_eq!(limits.search().effective_rate(), 20.0);
+}
+
+#[test]
+fn test_idle_hosts_are_pruned() {
+    let limits = host_limiter();
+
+    for i in 0..MAX_TRACKED_HOSTS + 10 {
+        limits.for_host(&format!("host{}.example", i));
+    }
+
+    // Unused limiters are idle, so the map never grows past the cap
+    assert!(limits.tracked_hosts() <= MAX_TRACKED_HOSTS);
+}
+
+fn ledger_in(dir: &tempfile::TempDir) -> RateLedger {
+    RateLedger::new(dir.path().join("state").join("ratelimit.ledger"))
+}
+
+#[test]
+fn test_ledger_enforces_per_minute_budget() {
+    let dir = tempfile::tempdir().unwrap();
+    let ledger = ledger_in(&dir);
+    let t0 = std::time::SystemTime::now();
+
+    assert_eq!(ledger.try_reserve("search", 2, Duration::ZERO, t0).unwrap(), None);
+    assert_eq!(ledger.try_reserve("search", 2, Duration::ZERO, t0).unwrap(), None);
+    assert_eq!(
+        ledger
+            .try_reserve("search", 2, Duration::ZERO, t0 + Duration::from_secs(10))
+            .unwrap(),
+        Some(Duration::from_secs(50))
+    );
+
+    // Entries older than a minute no longer count
+    assert_eq!(
+        ledger
+            .try_reserve("search", 2, Duration::ZERO, t0 + Duration::from_secs(61))
+            .unwrap(),
+        None
+    );
+}
+
+#[test]
+fn test_ledger_enforces_min_delay() {
+    let dir = tempfile::tempdir().unwrap();
+    let ledger = ledger_in(&dir);
+    let t0 = std::time::SystemTime::now();
+    let delay = Duration::from_secs(3);
+
+    assert_eq!(ledger.try_reserve("search", 20, delay, t0).unwrap(), None);
+    assert_eq!(
+        ledger
+            .try_reserve("search", 20, delay, t0 + Duration::from_secs(1))
+            .unwrap(),
+        Some(Duration::from_secs(2))
+    );
+    assert_eq!(
+        ledger
+            .try_reserve("search", 20, delay, t0 + Duration::from_secs(3))
+            .unwrap(),
+        None
+    );
+}
+
+#[test]
+fn test_ledger_is_shared_between_instances_and_keyed() {
+    let dir = tempfile::tempdir().unwrap();
+    let first = ledger_in(&dir);
+    let second = ledger_in(&dir);
+    let now = std::time::SystemTime::now();
+
+    assert_eq!(first.try_reserve("search", 1, Duration::ZERO, now).unwrap(), None);
+    assert!(second.try_reserve("search", 1, Duration::ZERO, now).unwrap().is_some());
+    // Other budgets are tracked separately
+    assert_eq!(second.try_reserve("other", 1, Duration::ZERO, now).unwrap(), None);
+}
+
+#[test]
+fn test_ledger_serializes_concurrent_writers() {
+    let dir = tempfile::tempdir().unwrap();
+    let now = std::time::SystemTime::now();
+
+    let granted: usize = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let ledger = ledger_in(&dir);
+                scope.spawn(move || {
+                    ledger
+                        .try_reserve("search", 5, Duration::ZERO, now)
+                        .unwrap()
+                        .is_none()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|h| h.join().unwrap() as usize)
+            .sum()
+    });
+
+    assert_eq!(granted, 5);
+}
+
+#[tokio::test]
+async fn test_limiters_sharing_a_ledger_respect_one_budget() {
+    let dir = tempfile::tempdir().unwrap();
+    // Each "process" alone would allow requests immediately
+    let config = RateLimitConfig::new(600, Duration::from_millis(200)).with_burst(10);
+    let first = RateLimiter::with_config(&config).with_ledger(ledger_in(&dir), "search");
+    let second = RateLimiter::with_config(&config).with_ledger(ledger_in(&dir), "search");
+    let start = std::time::Instant::now();
+
+    first.acquire().await;
+    second.acquire().await;
+
+    assert!(start.elapsed() >= Duration::from_millis(190));
+}
@@ -0,0 +1,162 @@
+//! Retry policy with exponential backoff and jitter for transient HTTP failures
+
+use crate::models::RetryInfo;
+use crate::ssrf;
+use anyhow::Result;
+use reqwest::header::RETRY_AFTER;
+use reqwest::{RequestBuilder, Response, StatusCode};
+use std::future::Future;
+use std::time::{Duration, SystemTime};
+
+/// Retry policy applied to outgoing HTTP requests
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total attempts including the first one (1 disables retrying)
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled for every further retry
+    pub base_delay: Duration,
+    /// Upper bound for a single delay, including one requested via Retry-After
+    pub max_delay: Duration,
+    /// Fraction of each delay that is randomized (0.0 = none, 1.0 = full jitter)
+    pub jitter: f64,
+    /// HTTP status codes treated as transient
+    pub retryable_statuses: Vec<u16>,
+    /// Wait as long as the server's Retry-After header asks (capped at `max_delay`)
+    pub respect_retry_after: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+            jitter: 0.5,
+            retryable_statuses: vec![408, 429, 500, 502, 503, 504],
+            respect_retry_after: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: u32, base_delay: Duration) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            base_delay,
+            ..Self::default()
+        }
+    }
+
+    /// Policy that makes exactly one attempt
+    pub fn disabled() -> Self {
+        Self::new(1, Duration::ZERO)
+    }
+
+    pub fn is_retryable_status(&self, status: StatusCode) -> bool {
+        self.retryable_statuses.contains(&status.as_u16())
+    }
+
+    /// Connection failures, resets and timeouts are transient; builder,
+    /// redirect and decode errors, and destinations refused by the SSRF
+    /// policy, are not
+    pub fn is_retryable_error(err: &reqwest::Error) -> bool {
+        (err.is_timeout() || err.is_connect() || err.is_request())
+            && ssrf::blocked_reason(err).is_none()
+    }
+
+    /// Delay before the given retry (1 = first retry), with jitter applied
+    pub fn backoff_delay(&self, retry: u32) -> Duration {
+        let exp = retry.saturating_sub(1).min(16);
+        let delay = self
+            .base_delay
+            .saturating_mul(1 << exp)
+            .min(self.max_delay);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        delay.mul_f64(1.0 - jitter * fastrand::f64())
+    }
+
+    /// Parse a Retry-After value given either as seconds or as an HTTP date
+    pub fn parse_retry_after(value: &str) -> Option<Duration> {
+        let value = value.trim();
+        if let Ok(secs) = value.parse::<u64>() {
+            return Some(Duration::from_secs(secs));
+        }
+        let date = httpdate::parse_http_date(value).ok()?;
+        Some(
+            date.duration_since(SystemTime::now())
+                .unwrap_or(Duration::ZERO),
+        )
+    }
+
+    /// Send a request, rebuilding and resending it while failures are transient.
+    ///
+    /// When attempts run out on a retryable status the last response is
+    /// returned as-is so callers can still report the HTTP error.
+    pub async fn send<F>(&self, build: F) -> Result<(Response, RetryInfo)>
+    where
+        F: Fn() -> RequestBuilder,
+    {
+        self.send_paced(|| async {}, build).await
+    }
+
+    /// Like [`send`](Self::send), awaiting `pace` before every attempt.
+    ///
+    /// Callers pass their rate limiter here so a retry takes its own permit
+    /// instead of riding on the one granted to the first attempt.
+    pub async fn send_paced<P, Fut, F>(&self, pace: P, build: F) -> Result<(Response, RetryInfo)>
+    where
+        P: Fn() -> Fut,
+        Fut: Future<Output = ()>,
+        F: Fn() -> RequestBuilder,
+    {
+        let max_attempts = self.max_attempts.max(1);
+        let mut info = RetryInfo::default();
+
+        loop {
+            info.attempts += 1;
+            let last = info.attempts >= max_attempts;
+
+            pace().await;
+            let (reason, retry_after) = match build().send().await {
+                Ok(resp) if !last && self.is_retryable_status(resp.status()) => {
+                    info.throttled |= resp.status() == StatusCode::TOO_MANY_REQUESTS;
+                    let retry_after = if self.respect_retry_after {
+                        resp.headers()
+                            .get(RETRY_AFTER)
+                            .and_then(|v| v.to_str().ok())
+                            .and_then(Self::parse_retry_after)
+                    } else {
+                        None
+                    };
+                    (format!("HTTP {}", resp.status().as_u16()), retry_after)
+                }
+                Ok(resp) => return Ok((resp, info)),
+                Err(e) if !last && Self::is_retryable_error(&e) => (describe_error(&e), None),
+                Err(e) if info.attempts > 1 => {
+                    return Err(anyhow::anyhow!("{} (after {} attempts)", e, info.attempts));
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            let delay = match retry_after {
+                Some(wait) => wait.min(self.max_delay),
+                None => self.backoff_delay(info.attempts),
+            };
+            info.reasons.push(reason);
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+fn describe_error(err: &reqwest::Error) -> String {
+    if err.is_timeout() {
+        "timeout".to_string()
+    } else if err.is_connect() {
+        "connection failed".to_string()
+    } else {
+        "connection error".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,192 @@
+//! Unit tests for retry module
+
+use super::*;
+use crate::test_server::{TestServer, http_response};
+
+fn fast_policy(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_delay: Duration::from_millis(50),
+        jitter: 0.0,
+        ..RetryPolicy::new(max_attempts, Duration::from_millis(1))
+    }
+}
+
+#[test]
+fn test_backoff_grows_exponentially_and_caps() {
+    let policy = RetryPolicy {
+        max_delay: Duration::from_millis(350),
+        jitter: 0.0,
+        ..RetryPolicy::new(5, Duration::from_millis(100))
+    };
+
+    assert_eq!(policy.backoff_delay(1), Duration::from_millis(100));
+    assert_eq!(policy.backoff_delay(2), Duration::from_millis(200));
+    assert_eq!(policy.backoff_delay(3), Duration::from_millis(350));
+    assert_eq!(policy.backoff_delay(30), Duration::from_millis(350));
+}
+
+#[test]
+fn test_backoff_jitter_stays_in_range() {
+    let policy = RetryPolicy {
+        jitter: 1.0,
+        ..RetryPolicy::new(3, Duration::from_millis(100))
+    };
+
+    for _ in 0..100 {
+        assert!(policy.backoff_delay(1) <= Duration::from_millis(100));
+    }
+}
+
+#[test]
+fn test_parse_retry_after() {
+    assert_eq!(RetryPolicy::parse_retry_after("5"), Some(Duration::from_secs(5)));
+    assert_eq!(RetryPolicy::parse_retry_after(" 0 "), Some(Duration::ZERO));
+    // Dates in the past mean "retry now"
+    assert_eq!(
+        RetryPolicy::parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+        Some(Duration::ZERO)
+    );
+    assert_eq!(RetryPolicy::parse_retry_after("soon"), None);
+}
+
+#[test]
+fn test_retryable_statuses() {
+    let policy = RetryPolicy::default();
+    assert!(policy.is_retryable_status(StatusCode::SERVICE_UNAVAILABLE));
+    assert!(policy.is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+    assert!(!policy.is_retryable_status(StatusCode::NOT_FOUND));
+    assert!(!policy.is_retryable_status(StatusCode::OK));
+}
+
+#[tokio::test]
+async fn test_send_retries_until_success() {
+    let server = TestServer::start(vec![
+        http_response(503, &[], "busy"),
+        http_response(502, &[], "bad gateway"),
+        http_response(200, &[], "ok"),
+    ])
+    .await;
+    let client = reqwest::Client::new();
+    let url = server.url("/");
+
+    let (resp, info) = fast_policy(3).send(|| client.get(&url)).await.unwrap();
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(info.attempts, 3);
+    assert_eq!(info.reasons, vec!["HTTP 503", "HTTP 502"]);
+    assert!(!info.throttled);
+    assert!(info.note().unwrap().contains("3 attempts"));
+    assert_eq!(server.hits(), 3);
+}
+
+#[tokio::test]
+async fn test_send_paced_waits_before_every_attempt() {
+    let server = TestServer::start(vec![
+        http_response(429, &[], "slow down"),
+        http_response(503, &[], "busy"),
+        http_response(200, &[], "ok"),
+    ])
+    .await;
+    let client = reqwest::Client::new();
+    let url = server.url("/");
+    let paced = std::sync::atomic::AtomicU32::new(0);
+
+    let (resp, info) = fast_policy(3)
+        .send_paced(
+            || async {
+                paced.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            },
+            || client.get(&url),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(info.attempts, 3);
+    assert_eq!(paced.into_inner(), 3);
+    assert!(info.throttled);
+    assert_eq!(server.hits(), 3);
+}
+
+#[tokio::test]
+async fn test_send_returns_last_response_when_exhausted() {
+    let server = TestServer::start(vec![http_response(503, &[], "busy")]).await;
+    let client = reqwest::Client::new();
+    let url = server.url("/");
+
+    let (resp, info) = fast_policy(2).send(|| client.get(&url)).await.unwrap();
+
+    assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(info.attempts, 2);
+    assert_eq!(server.hits(), 2);
+}
+
+#[tokio::test]
+async fn test_send_does_not_retry_client_errors() {
+    let server = TestServer::start(vec![http_response(404, &[], "missing")]).await;
+    let client = reqwest::Client::new();
+    let url = server.url("/");
+
+    let (resp, info) = fast_policy(3).send(|| client.get(&url)).await.unwrap();
+
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    assert!(!info.retried());
+    assert!(info.note().is_none());
+    assert_eq!(server.hits(), 1);
+}
+
+#[tokio::test]
+async fn test_send_honors_retry_after() {
+    let server = TestServer::start(vec![
+        http_response(429, &[("Retry-After", "1")], "slow down"),
+        http_response(200, &[], "ok"),
+    ])
+    .await;
+    let client = reqwest::Client::new();
+    let url = server.url("/");
+    let policy = RetryPolicy {
+        max_delay: Duration::from_secs(2),
+        ..fast_policy(2)
+    };
+
+    let started = std::time::Instant::now();
+    let (resp, info) = policy.send(|| client.get(&url)).await.unwrap();
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(info.reasons, vec!["HTTP 429"]);
+    assert!(started.elapsed() >= Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn test_send_retries_connection_errors() {
+    // Bind then drop a listener so the port refuses connections
+    let addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let client = reqwest::Client::new();
+    let url = format!("http://{}/", addr);
+
+    let err = fast_policy(2)
+        .send(|| client.get(&url))
+        .await
+        .unwrap_err();
+
+    assert!(err.to_string().contains("after 2 attempts"));
+}
+
+#[tokio::test]
+async fn test_disabled_policy_makes_one_attempt() {
+    let server = TestServer::start(vec![http_response(503, &[], "busy")]).await;
+    let client = reqwest::Client::new();
+    let url = server.url("/");
+
+    let (resp, info) = RetryPolicy::disabled()
+        .send(|| client.get(&url))
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(info.attempts, 1);
+    assert_eq!(server.hits(), 1);
+}
@@ -0,0 +1,73 @@
+//! Response bodies read in chunks up to a size limit
+
+use super::document::format_size;
+
+/// Why a response body could not be read
+#[derive(Debug, thiserror::Error)]
+pub enum BodyError {
+    #[error(
+        "Response from {url} is too large: Content-Length is {}, over the {} limit",
+        format_size(*.size),
+        format_size(*.limit)
+    )]
+    DeclaredTooLarge { url: String, size: u64, limit: u64 },
+    #[error(
+        "Response from {url} is too large: stopped reading at {}, over the {} limit",
+        format_size(*.read),
+        format_size(*.limit)
+    )]
+    TooLarge { url: String, read: u64, limit: u64 },
+    #[error("Failed to read response from {url}: {source}")]
+    Read {
+        url: String,
+        #[source]
+        source: reqwest::Error,
+    },
+}
+
+/// Read a body without ever holding more than `limit` bytes of it.
+///
+/// A declared Content-Length over the limit fails before anything is read;
+/// otherwise reading stops at the first chunk that crosses the limit.
+pub async fn read_limited(mut resp: reqwest::Response, limit: u64) -> Result<Vec<u8>, BodyError> {
+    let url = resp.url().to_string();
+    if let Some(size) = resp.content_length()
+        && size > limit
+    {
+        return Err(BodyError::DeclaredTooLarge { url, size, limit });
+    }
+
+    let mut body = Vec::with_capacity(resp.content_length().unwrap_or(0) as usize);
+    loop {
+        let chunk = match resp.chunk().await {
+            Ok(Some(chunk)) => chunk,
+            Ok(None) => return Ok(body),
+            Err(source) => return Err(BodyError::Read { url, source }),
+        };
+        let read = (body.len() + chunk.len()) as u64;
+        if read > limit {
+            return Err(BodyError::TooLarge { url, read, limit });
+        }
+        body.extend_from_slice(&chunk);
+    }
+}
+
+/// Read at most `limit` bytes of a body, ignoring the rest; the flag is set
+/// when the body was cut short
+pub async fn read_prefix(mut resp: reqwest::Response, limit: u64) -> Result<(Vec<u8>, bool), BodyError> {
+    let url = resp.url().to_string();
+    let limit = limit as usize;
+    let mut body = Vec::with_capacity(resp.content_length().unwrap_or(0).min(limit as u64) as usize);
+    loop {
+        let chunk = match resp.chunk().await {
+            Ok(Some(chunk)) => chunk,
+            Ok(None) => return Ok((body, false)),
+            Err(source) => return Err(BodyError::Read { url, source }),
+        };
+        if body.len() + chunk.len() > limit {
+            body.extend_from_slice(&chunk[..limit - body.len()]);
+            return Ok((body, true));
+        }
+        body.extend_from_slice(&chunk);
+    }
+}
@@ -0,0 +1,259 @@
+//! Document type detection for fetched responses
+//!
+//! The Content-Type header decides when it is specific; missing or generic
+//! types (`application/octet-stream`) are resolved by sniffing the first
+//! bytes, the way browsers do.
+
+use super::jsonpath::JsonPath;
+use super::pdf;
+use crate::models::ContentFormat;
+use anyhow::Result;
+use serde_json::Value;
+
+/// How many leading bytes are inspected when sniffing
+const SNIFF_BYTES: usize = 1024;
+
+/// What a fetched body is and how it gets rendered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DocumentKind {
+    Html,
+    Pdf,
+    Json,
+    Xml,
+    Markdown,
+    Text,
+    /// Images, archives, executables and other content that cannot be read as text
+    Binary(String),
+}
+
+impl DocumentKind {
+    /// MIME type recorded for a sniffed document, so cached copies render the same way
+    pub fn mime_type(&self) -> &str {
+        match self {
+            Self::Html => "text/html",
+            Self::Pdf => pdf::MIME_TYPE,
+            Self::Json => "application/json",
+            Self::Xml => "application/xml",
+            Self::Markdown => "text/markdown",
+            Self::Text => "text/plain",
+            Self::Binary(mime) => mime,
+        }
+    }
+}
+
+/// application/* subtypes that are never readable text: archives, executables
+/// and other opaque byte formats
+const BINARY_APPLICATION_TYPES: &[&str] = &[
+    "zip",
+    "gzip",
+    "x-gzip",
+    "x-tar",
+    "x-bzip2",
+    "x-xz",
+    "zstd",
+    "x-7z-compressed",
+    "vnd.rar",
+    "x-rar-compressed",
+    "java-archive",
+    "vnd.android.package-archive",
+    "x-apple-diskimage",
+    "x-iso9660-image",
+    "x-executable",
+    "x-sharedlib",
+    "x-mach-binary",
+    "x-msdownload",
+    "x-msdos-program",
+    "vnd.microsoft.portable-executable",
+    "java-vm",
+    "wasm",
+    "ogg",
+];
+
+/// Kind declared by a Content-Type header, or None when it is missing or generic
+pub fn from_content_type(content_type: Option<&str>) -> Option<DocumentKind> {
+    let mime = content_type?.split(';').next()?.trim().to_ascii_lowercase();
+    let (kind, subtype) = mime.split_once('/')?;
+
+    Some(match (kind, subtype) {
+        (_, "octet-stream") | ("application", "unknown") => return None,
+        ("text", "html") | ("application", "xhtml+xml") => DocumentKind::Html,
+        ("application", "pdf") => DocumentKind::Pdf,
+        (_, "json") | (_, "x-json") => DocumentKind::Json,
+        (_, subtype) if subtype.ends_with("+json") => DocumentKind::Json,
+        (_, "xml") => DocumentKind::Xml,
+        (_, subtype) if subtype.ends_with("+xml") && kind != "image" => DocumentKind::Xml,
+        ("text", "markdown") | ("text", "x-markdown") => DocumentKind::Markdown,
+        ("text", _) => DocumentKind::Text,
+        (
+            "application",
+            "javascript" | "ecmascript" | "x-javascript" | "x-sh" | "yaml" | "x-yaml" | "toml"
+            | "sql" | "graphql" | "x-ndjson",
+        ) => DocumentKind::Text,
+        ("image" | "audio" | "video" | "font" | "model", _) => DocumentKind::Binary(mime.clone()),
+        ("application", subtype) if BINARY_APPLICATION_TYPES.contains(&subtype) => {
+            DocumentKind::Binary(mime.clone())
+        }
+        // Other application/* types (rtf, csv, x-tex, form data, ...) are often
+        // text; let the bytes decide
+        _ => return None,
+    })
+}
+
+/// Kind of a body, from its Content-Type or, failing that, its bytes
+pub fn classify(content_type: Option<&str>, bytes: &[u8]) -> DocumentKind {
+    match from_content_type(content_type) {
+        // PDFs are often served as text/html or octet-stream; the signature wins
+        Some(kind) if !bytes.starts_with(b"%PDF-") => kind,
+        _ => sniff(bytes),
+    }
+}
+
+/// Guess the kind of an undeclared body from its leading bytes
+pub fn sniff(bytes: &[u8]) -> DocumentKind {
+    if let Some(mime) = binary_signature(bytes) {
+        return if mime == pdf::MIME_TYPE {
+            DocumentKind::Pdf
+        } else {
+            DocumentKind::Binary(mime.to_string())
+        };
+    }
+
+    let head = &bytes[..bytes.len().min(SNIFF_BYTES)];
+    // NUL bytes never appear in text documents (UTF-16 text carries a BOM)
+    if head.contains(&0) && encoding_rs::Encoding::for_bom(head).is_none() {
+        return DocumentKind::Binary("application/octet-stream".to_string());
+    }
+
+    let text = String::from_utf8_lossy(head);
+    let text = text.trim_start_matches('\u{feff}').trim_start();
+    let lower = text.to_ascii_lowercase();
+    if (text.starts_with('{') || text.starts_with('['))
+        && serde_json::from_slice::<Value>(bytes).is_ok()
+    {
+        DocumentKind::Json
+    } else if ["<!doctype html", "<html", "<head", "<body", "<div", "<p>", "<p ", "<!--"]
+        .iter()
+        .any(|tag| lower.starts_with(tag))
+        || lower.contains("<html")
+        || lower.contains("<body")
+    {
+        DocumentKind::Html
+    } else if lower.starts_with("<?xml")
+        || lower.starts_with("<rss")
+        || lower.starts_with("<feed")
+        || lower.starts_with("<rdf:rdf")
+    {
+        DocumentKind::Xml
+    } else {
+        DocumentKind::Text
+    }
+}
+
+/// MIME type of well-known binary formats, by magic number
+fn binary_signature(bytes: &[u8]) -> Option<&'static str> {
+    const SIGNATURES: &[(&[u8], &str)] = &[
+        (b"%PDF-", "application/pdf"),
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"\x00\x00\x01\x00", "image/x-icon"),
+        (b"PK\x03\x04", "application/zip"),
+        (b"\x1f\x8b", "application/gzip"),
+        (b"\xfd7zXZ\x00", "application/x-xz"),
+        (b"7z\xbc\xaf\x27\x1c", "application/x-7z-compressed"),
+        (b"Rar!\x1a\x07", "application/vnd.rar"),
+        (b"\x7fELF", "application/x-executable"),
+        (b"\xca\xfe\xba\xbe", "application/java-vm"),
+        (b"\x00asm", "application/wasm"),
+        (b"OggS", "audio/ogg"),
+        (b"fLaC", "audio/flac"),
+        (b"wOFF", "font/woff"),
+        (b"wOF2", "font/woff2"),
+    ];
+
+    // Short printable magics also start ordinary text ("BMW", "MZ..."); the
+    // real headers always carry NUL bytes right after them
+    const WEAK_SIGNATURES: &[(&[u8], &str)] = &[
+        (b"BM", "image/bmp"),
+        (b"MZ", "application/x-msdownload"),
+        (b"ID3", "audio/mpeg"),
+    ];
+
+    if let Some((_, mime)) = SIGNATURES.iter().find(|(magic, _)| bytes.starts_with(magic)) {
+        return Some(mime);
+    }
+    let head = &bytes[..bytes.len().min(SNIFF_BYTES)];
+    if let Some((_, mime)) = WEAK_SIGNATURES.iter().find(|(magic, _)| bytes.starts_with(magic))
+        && head.contains(&0)
+    {
+        return Some(mime);
+    }
+    // "BZh", the block size digit, then the first block's magic
+    if bytes.starts_with(b"BZh") && bytes.get(4..10) == Some(b"1AY&SY") {
+        return Some("application/x-bzip2");
+    }
+    if bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WEBP") {
+        return Some("image/webp");
+    }
+    if bytes.get(4..8) == Some(b"ftyp") {
+        return Some("video/mp4");
+    }
+    None
+}
+
+/// Error message for a refused binary download
+pub fn binary_refusal(url: &str, mime: &str, size: Option<u64>) -> String {
+    let size = size.map_or_else(|| "unknown size".to_string(), format_size);
+    format!(
+        "Refusing to read binary content from {}: {} ({}). Only HTML, PDF, JSON, XML and text documents can be read.",
+        url, mime, size
+    )
+}
+
+/// Human-readable byte count, e.g. "12.3 KB"
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["KB", "MB", "GB"];
+    if bytes < 1024 {
+        return format!("{} bytes", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Parse a JSONPath expression, naming it in the error
+pub fn parse_json_path(path: &str) -> Result<JsonPath> {
+    JsonPath::parse(path).map_err(|e| anyhow::anyhow!("Invalid JSONPath '{}': {}", path, e))
+}
+
+/// Pretty-printed JSON, optionally narrowed to the values a JSONPath selects.
+///
+/// Bodies that are not valid JSON are returned unchanged.
+pub fn render_json(body: &str, path: Option<&str>, format: ContentFormat) -> Result<String> {
+    let Ok(value) = serde_json::from_str::<Value>(body) else {
+        return Ok(body.trim().to_string());
+    };
+
+    let (intro, selected) = match path {
+        Some(path) => {
+            let matches = parse_json_path(path)?.query(&value);
+            let intro = match matches.len() {
+                1 => format!("JSONPath `{}` matched 1 value:\n\n", path),
+                n => format!("JSONPath `{}` matched {} values:\n\n", path, n),
+            };
+            (intro, Value::Array(matches.into_iter().cloned().collect()))
+        }
+        None => (String::new(), value),
+    };
+
+    let pretty = serde_json::to_string_pretty(&selected)?;
+    Ok(match format {
+        ContentFormat::Markdown => format!("{}```json\n{}\n```", intro, pretty),
+        _ => format!("{}{}", intro, pretty),
+    })
+}
@@ -0,0 +1,93 @@
+//! Character encoding detection for fetched pages
+//!
+//! Follows the order browsers use: a byte order mark wins, then the
+//! Content-Type charset, then a `<meta>` (or XML) declaration near the start
+//! of the document. Pages that declare nothing are decoded as UTF-8 when
+//! valid and otherwise guessed from their bytes.
+
+use encoding_rs::{Encoding, UTF_8, WINDOWS_1252};
+
+/// How far into the body to look for a `<meta charset>` declaration
+const PRESCAN_BYTES: usize = 4096;
+
+/// Decode a response body to UTF-8
+pub fn decode(bytes: &[u8], content_type: Option<&str>) -> String {
+    let encoding = detect(bytes, content_type);
+    // `decode` strips a BOM matching the detected encoding
+    let (text, _, _) = encoding.decode(bytes);
+    text.into_owned()
+}
+
+/// The encoding a body is written in
+pub fn detect(bytes: &[u8], content_type: Option<&str>) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        return encoding;
+    }
+    if let Some(encoding) = content_type.and_then(header_charset) {
+        return encoding;
+    }
+    if let Some(encoding) = declared_charset(&bytes[..bytes.len().min(PRESCAN_BYTES)]) {
+        return encoding;
+    }
+    if std::str::from_utf8(bytes).is_ok() {
+        return UTF_8;
+    }
+
+    let mut detector = chardetng::EncodingDetector::new();
+    detector.feed(bytes, true);
+    detector.guess(None, true)
+}
+
+/// `charset` parameter of a Content-Type header
+fn header_charset(content_type: &str) -> Option<&'static Encoding> {
+    content_type.split(';').skip(1).find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        if !name.trim().eq_ignore_ascii_case("charset") {
+            return None;
+        }
+        Encoding::for_label(value.trim().trim_matches(['"', '\'']).as_bytes())
+    })
+}
+
+/// Charset declared by `<meta charset>`, `<meta http-equiv="Content-Type">`
+/// or an XML declaration in the first bytes of the document
+fn declared_charset(head: &[u8]) -> Option<&'static Encoding> {
+    // Declarations are ASCII, so a lossy lowercase view is enough to find them
+    let head = String::from_utf8_lossy(head).to_ascii_lowercase();
+
+    let xml = head
+        .trim_start()
+        .strip_prefix("<?xml")
+        .and_then(|decl| attribute_value(&decl[..decl.find("?>")?], "encoding"));
+
+    let meta = head.match_indices("<meta").find_map(|(start, _)| {
+        let tag = &head[start..];
+        let tag = &tag[..tag.find('>').unwrap_or(tag.len())];
+        attribute_value(tag, "charset")
+    });
+
+    let encoding = Encoding::for_label(xml.or(meta)?.as_bytes())?;
+    // A page read as bytes cannot really be UTF-16 without a BOM, and
+    // x-user-defined is windows-1252 in practice (as browsers treat both)
+    Some(match encoding.name() {
+        "UTF-16LE" | "UTF-16BE" => UTF_8,
+        "x-user-defined" => WINDOWS_1252,
+        _ => encoding,
+    })
+}
+
+/// Value following `name=` in a tag, also inside `content="text/html; charset=..."`
+fn attribute_value<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    tag.match_indices(name).find_map(|(i, _)| {
+        let rest = tag[i + name.len()..].trim_start();
+        let rest = rest.strip_prefix('=')?.trim_start();
+        let rest = rest.trim_start_matches(['"', '\'']);
+        let end = rest
+            .find(|c: char| {
+                c == '"' || c == '\'' || c == ';' || c == '/' || c == '>' || c.is_whitespace()
+            })
+            .unwrap_or(rest.len());
+        let value = &rest[..end];
+        (!value.is_empty()).then_some(value)
+    })
+}
@@ -0,0 +1,155 @@
+//! Rendering of XML documents, with RSS and Atom feeds listed entry by entry
+
+use crate::models::ContentFormat;
+use roxmltree::{Document, Node};
+use scraper::Html;
+
+/// Longest entry summary shown, in characters
+const SUMMARY_CHARS: usize = 300;
+
+/// Feeds as a numbered entry list; other XML verbatim
+pub fn render(xml: &str, format: ContentFormat) -> String {
+    let xml = xml.trim();
+    if let Ok(document) = Document::parse(xml)
+        && let Some(feed) = read_feed(document.root_element())
+    {
+        return feed;
+    }
+
+    match format {
+        ContentFormat::Markdown => format!("```xml\n{}\n```", xml),
+        _ => xml.to_string(),
+    }
+}
+
+struct Entry {
+    title: Option<String>,
+    link: Option<String>,
+    date: Option<String>,
+    summary: Option<String>,
+}
+
+/// RSS 2.0, RSS 1.0 (RDF) or Atom, by root element
+fn read_feed(root: Node) -> Option<String> {
+    let (channel, entries): (Node, Vec<Entry>) = match root.tag_name().name() {
+        "rss" => {
+            let channel = child(root, "channel")?;
+            let items = children(channel, "item").map(rss_entry).collect();
+            (channel, items)
+        }
+        // RSS 1.0 keeps items next to the channel rather than inside it
+        "RDF" => {
+            let channel = child(root, "channel")?;
+            let items = children(root, "item").map(rss_entry).collect();
+            (channel, items)
+        }
+        "feed" => (root, children(root, "entry").map(atom_entry).collect()),
+        _ => return None,
+    };
+
+    let mut output = String::new();
+    if let Some(title) = text(channel, "title") {
+        output.push_str(&format!("Feed: {}\n", title));
+    }
+    if let Some(link) = text(channel, "link").or_else(|| atom_link(channel)) {
+        output.push_str(&format!("Link: {}\n", link));
+    }
+    if let Some(description) = text(channel, "description").or_else(|| text(channel, "subtitle")) {
+        output.push_str(&format!("Description: {}\n", summarize(&description)));
+    }
+    if let Some(updated) = text(channel, "lastBuildDate").or_else(|| text(channel, "updated")) {
+        output.push_str(&format!("Updated: {}\n", updated));
+    }
+
+    output.push_str(&format!(
+        "\n{} {}:\n",
+        entries.len(),
+        if entries.len() == 1 { "entry" } else { "entries" }
+    ));
+    for (i, entry) in entries.iter().enumerate() {
+        output.push_str(&format!(
+            "\n{}. {}\n",
+            i + 1,
+            entry.title.as_deref().unwrap_or("(untitled)")
+        ));
+        if let Some(link) = &entry.link {
+            output.push_str(&format!("   URL: {}\n", link));
+        }
+        if let Some(date) = &entry.date {
+            output.push_str(&format!("   Published: {}\n", date));
+        }
+        if let Some(summary) = &entry.summary {
+            output.push_str(&format!("   Summary: {}\n", summary));
+        }
+    }
+
+    Some(output.trim_end().to_string())
+}
+
+fn rss_entry(item: Node) -> Entry {
+    Entry {
+        title: text(item, "title"),
+        link: text(item, "link").or_else(|| text(item, "guid")),
+        date: text(item, "pubDate").or_else(|| text(item, "date")),
+        summary: text(item, "description")
+            .or_else(|| text(item, "encoded"))
+            .map(|s| summarize(&s)),
+    }
+}
+
+fn atom_entry(entry: Node) -> Entry {
+    Entry {
+        title: text(entry, "title"),
+        link: atom_link(entry),
+        date: text(entry, "published").or_else(|| text(entry, "updated")),
+        summary: text(entry, "summary")
+            .or_else(|| text(entry, "content"))
+            .map(|s| summarize(&s)),
+    }
+}
+
+/// `<link href>` with rel="alternate" (or no rel), as Atom uses
+fn atom_link(node: Node) -> Option<String> {
+    children(node, "link")
+        .find(|link| link.attribute("rel").is_none_or(|rel| rel == "alternate"))
+        .and_then(|link| link.attribute("href"))
+        .map(str::to_string)
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    children(node, name).next()
+}
+
+/// Element children by local name, ignoring namespaces
+fn children<'a, 'input>(
+    node: Node<'a, 'input>,
+    name: &str,
+) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children()
+        .filter(move |n| n.is_element() && n.tag_name().name() == name)
+}
+
+/// Trimmed text of the first child element with this name
+fn text(node: Node, name: &str) -> Option<String> {
+    let element = child(node, name)?;
+    let text: String = element
+        .descendants()
+        .filter(|n| n.is_text())
+        .filter_map(|n| n.text())
+        .collect();
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Plain text of an HTML-bearing description, shortened for the listing
+fn summarize(html: &str) -> String {
+    let fragment = Html::parse_fragment(html);
+    let text = fragment.root_element().text().collect::<Vec<_>>().join(" ");
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    if text.chars().count() <= SUMMARY_CHARS {
+        return text;
+    }
+    let short: String = text.chars().take(SUMMARY_CHARS).collect();
+    format!("{}...", short.trim_end())
+}
@@ -0,0 +1,225 @@
+//! A JSONPath subset for narrowing JSON documents
+//!
+//! Supports the root `$`, member names (`.name`, `['name']`), array indices
+//! (`[0]`, `[-1]`), slices (`[1:3]`), unions (`[0,2]`, `['a','b']`),
+//! wildcards (`.*`, `[*]`) and recursive descent (`..name`). Filter
+//! expressions (`[?(...)]`) are not supported.
+
+use serde_json::Value;
+
+/// A parsed JSONPath expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonPath {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    /// Direct children of each node
+    Child(Vec<Step>),
+    /// Each node and all of its descendants (`..`)
+    Descendant(Vec<Step>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    Name(String),
+    Index(i64),
+    Slice(Option<i64>, Option<i64>),
+    Wildcard,
+}
+
+impl JsonPath {
+    /// Parse an expression such as `$.items[*].name`, describing the first problem on failure
+    pub fn parse(path: &str) -> Result<Self, String> {
+        let chars: Vec<char> = path.trim().chars().collect();
+        if chars.first() != Some(&'$') {
+            return Err("must start with '$'".to_string());
+        }
+
+        let mut segments = Vec::new();
+        let mut pos = 1;
+        while pos < chars.len() {
+            match chars[pos] {
+                '.' if chars.get(pos + 1) == Some(&'.') => {
+                    pos += 2;
+                    let steps = match chars.get(pos) {
+                        Some('[') => parse_brackets(&chars, &mut pos)?,
+                        _ => vec![parse_dotted(&chars, &mut pos)?],
+                    };
+                    segments.push(Segment::Descendant(steps));
+                }
+                '.' => {
+                    pos += 1;
+                    segments.push(Segment::Child(vec![parse_dotted(&chars, &mut pos)?]));
+                }
+                '[' => segments.push(Segment::Child(parse_brackets(&chars, &mut pos)?)),
+                c => return Err(format!("unexpected '{}' at position {}", c, pos)),
+            }
+        }
+
+        Ok(Self { segments })
+    }
+
+    /// Every value the expression selects, in document order
+    pub fn query<'a>(&self, root: &'a Value) -> Vec<&'a Value> {
+        let mut nodes = vec![root];
+        for segment in &self.segments {
+            nodes = match segment {
+                Segment::Child(steps) => nodes.into_iter().flat_map(|n| select(n, steps)).collect(),
+                Segment::Descendant(steps) => nodes
+                    .into_iter()
+                    .flat_map(descendants)
+                    .flat_map(|n| select(n, steps))
+                    .collect(),
+            };
+        }
+        nodes
+    }
+}
+
+/// A name or `*` after a dot
+fn parse_dotted(chars: &[char], pos: &mut usize) -> Result<Step, String> {
+    if chars.get(*pos) == Some(&'*') {
+        *pos += 1;
+        return Ok(Step::Wildcard);
+    }
+    let start = *pos;
+    while *pos < chars.len() && !matches!(chars[*pos], '.' | '[') {
+        *pos += 1;
+    }
+    if start == *pos {
+        return Err(format!("expected a member name at position {}", start));
+    }
+    Ok(Step::Name(chars[start..*pos].iter().collect()))
+}
+
+/// A bracketed, comma-separated list of names, indices, slices or `*`
+fn parse_brackets(chars: &[char], pos: &mut usize) -> Result<Vec<Step>, String> {
+    *pos += 1;
+    let mut steps = Vec::new();
+    loop {
+        skip_spaces(chars, pos);
+        let step = match chars.get(*pos) {
+            None => return Err("unclosed '['".to_string()),
+            Some('?') => return Err("filter expressions are not supported".to_string()),
+            Some('*') => {
+                *pos += 1;
+                Step::Wildcard
+            }
+            Some(&quote @ ('\'' | '"')) => Step::Name(parse_quoted(chars, pos, quote)?),
+            Some(_) => {
+                let start = *pos;
+                while *pos < chars.len() && !matches!(chars[*pos], ',' | ']') {
+                    *pos += 1;
+                }
+                parse_index(chars[start..*pos].iter().collect::<String>().trim())?
+            }
+        };
+        steps.push(step);
+
+        skip_spaces(chars, pos);
+        match chars.get(*pos) {
+            Some(',') => *pos += 1,
+            Some(']') => {
+                *pos += 1;
+                return Ok(steps);
+            }
+            Some(c) => return Err(format!("unexpected '{}' at position {}", c, pos)),
+            None => return Err("unclosed '['".to_string()),
+        }
+    }
+}
+
+fn parse_quoted(chars: &[char], pos: &mut usize, quote: char) -> Result<String, String> {
+    *pos += 1;
+    let mut name = String::new();
+    while let Some(&c) = chars.get(*pos) {
+        *pos += 1;
+        match c {
+            '\\' => match chars.get(*pos) {
+                Some(&escaped) => {
+                    name.push(escaped);
+                    *pos += 1;
+                }
+                None => break,
+            },
+            c if c == quote => return Ok(name),
+            c => name.push(c),
+        }
+    }
+    Err("unterminated string".to_string())
+}
+
+/// `3`, `-1`, or a slice such as `1:3`, `:2` or `-2:`
+fn parse_index(text: &str) -> Result<Step, String> {
+    let number = |s: &str| -> Result<Option<i64>, String> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Ok(None);
+        }
+        s.parse()
+            .map(Some)
+            .map_err(|_| format!("'{}' is not an index", s))
+    };
+
+    match text.split(':').collect::<Vec<_>>().as_slice() {
+        [index] => number(index)?
+            .map(Step::Index)
+            .ok_or_else(|| "empty brackets".to_string()),
+        [start, end] => Ok(Step::Slice(number(start)?, number(end)?)),
+        _ => Err(format!("slice steps are not supported in '{}'", text)),
+    }
+}
+
+fn skip_spaces(chars: &[char], pos: &mut usize) {
+    while chars.get(*pos).is_some_and(|c| c.is_whitespace()) {
+        *pos += 1;
+    }
+}
+
+/// Children of one node picked by each step, in step order
+fn select<'a>(node: &'a Value, steps: &[Step]) -> Vec<&'a Value> {
+    let mut selected = Vec::new();
+    for step in steps {
+        match (step, node) {
+            (Step::Name(name), Value::Object(map)) => selected.extend(map.get(name)),
+            (Step::Index(index), Value::Array(items)) => {
+                selected.extend(resolve(*index, items.len()).and_then(|i| items.get(i)))
+            }
+            (Step::Slice(start, end), Value::Array(items)) => {
+                let len = items.len() as i64;
+                let clamp = |i: i64| if i < 0 { (len + i).max(0) } else { i.min(len) };
+                let start = start.map_or(0, clamp) as usize;
+                let end = end.map_or(len, clamp) as usize;
+                if start < end {
+                    selected.extend(&items[start..end]);
+                }
+            }
+            (Step::Wildcard, Value::Array(items)) => selected.extend(items),
+            (Step::Wildcard, Value::Object(map)) => selected.extend(map.values()),
+            _ => {}
+        }
+    }
+    selected
+}
+
+/// Array position of a possibly negative index
+fn resolve(index: i64, len: usize) -> Option<usize> {
+    if index < 0 {
+        len.checked_sub(index.unsigned_abs() as usize)
+    } else {
+        Some(index as usize)
+    }
+}
+
+/// A node followed by all of its descendants, depth first
+fn descendants(node: &Value) -> Vec<&Value> {
+    let mut all = vec![node];
+    match node {
+        Value::Array(items) => all.extend(items.iter().flat_map(descendants)),
+        Value::Object(map) => all.extend(map.values().flat_map(descendants)),
+        _ => {}
+    }
+    all
+}
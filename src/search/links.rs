@@ -0,0 +1,124 @@
+//! Hyperlink extraction for fetched pages
+
+use crate::models::{LinkOptions, LinkScope, PageLink};
+use reqwest::Url;
+use scraper::{ElementRef, Html, Selector};
+
+/// Every distinct http(s) link on the page, in document order.
+///
+/// Links are resolved against `<base href>` when present, otherwise the page
+/// URL. Script, mail and other non-web links are skipped, and a URL that
+/// appears several times is listed once with the first non-empty anchor text.
+pub fn extract(html: &str, url: &str) -> Vec<PageLink> {
+    let document = Html::parse_document(html);
+    let Ok(page_url) = Url::parse(url) else {
+        return Vec::new();
+    };
+
+    let base_sel = Selector::parse("base[href]").unwrap();
+    let base = document
+        .select(&base_sel)
+        .next()
+        .and_then(|el| page_url.join(el.value().attr("href")?.trim()).ok())
+        .unwrap_or_else(|| page_url.clone());
+
+    let link_sel = Selector::parse("a[href], area[href]").unwrap();
+    let mut links: Vec<PageLink> = Vec::new();
+
+    for el in document.select(&link_sel) {
+        let Some(href) = el.value().attr("href") else {
+            continue;
+        };
+        let Ok(target) = base.join(href.trim()) else {
+            continue;
+        };
+        if !matches!(target.scheme(), "http" | "https") {
+            continue;
+        }
+
+        let text = anchor_text(el);
+        let internal = same_site(&page_url, &target);
+        let target = String::from(target);
+        if let Some(existing) = links.iter_mut().find(|link| link.url == target) {
+            if existing.text.is_empty() {
+                existing.text = text;
+            }
+            continue;
+        }
+
+        links.push(PageLink {
+            internal,
+            url: target,
+            text,
+            rel: el
+                .value()
+                .attr("rel")
+                .map(|rel| {
+                    rel.split_whitespace()
+                        .map(|r| r.to_ascii_lowercase())
+                        .collect()
+                })
+                .unwrap_or_default(),
+        });
+    }
+
+    links
+}
+
+/// Whether a link passes the scope and URL pattern filters
+pub fn matches(link: &PageLink, options: &LinkOptions) -> bool {
+    let in_scope = match options.scope {
+        LinkScope::All => true,
+        LinkScope::Internal => link.internal,
+        LinkScope::External => !link.internal,
+    };
+    in_scope
+        && options
+            .pattern
+            .as_deref()
+            .is_none_or(|pattern| matches_pattern(&link.url, pattern))
+}
+
+/// Case-insensitive substring match where `*` matches any run of characters
+fn matches_pattern(url: &str, pattern: &str) -> bool {
+    let url = url.to_lowercase();
+    let pattern = pattern.trim().to_lowercase();
+
+    let mut rest = url.as_str();
+    for part in pattern.split('*').filter(|p| !p.is_empty()) {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+fn anchor_text(el: ElementRef) -> String {
+    let text = el.text().collect::<Vec<_>>().join(" ");
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if !text.is_empty() {
+        return text;
+    }
+
+    // Image links: fall back to the image's alt text, then title attributes
+    let img_sel = Selector::parse("img[alt]").unwrap();
+    el.select(&img_sel)
+        .filter_map(|img| img.value().attr("alt"))
+        .chain(el.value().attr("aria-label"))
+        .chain(el.value().attr("title"))
+        .chain(el.value().attr("alt"))
+        .map(str::trim)
+        .find(|t| !t.is_empty())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Same host, ignoring case and a leading "www."
+fn same_site(page: &Url, target: &Url) -> bool {
+    let host = |url: &Url| {
+        url.host_str()
+            .map(|h| h.trim_start_matches("www.").to_ascii_lowercase())
+    };
+    host(page).is_some() && host(page) == host(target)
+}
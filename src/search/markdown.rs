@@ -0,0 +1,445 @@
+//! HTML to Markdown conversion for fetched pages
+//!
+//! Keeps the structure LLMs rely on when reading documentation: headings,
+//! emphasis, nested lists, fenced code blocks with language hints,
+//! blockquotes, tables, images and links (resolved against the page URL).
+
+use reqwest::Url;
+use scraper::{ElementRef, Html, Node};
+use std::cell::Cell;
+
+/// Nesting depth past which content is flattened to plain text, so hostile
+/// pages cannot exhaust the stack
+const MAX_DEPTH: usize = 128;
+
+/// Elements whose content is never rendered
+const SKIPPED_TAGS: &[&str] = &[
+    "script", "style", "noscript", "template", "head", "title", "meta", "link", "svg", "iframe",
+    "object", "embed", "canvas", "button", "select", "input", "textarea",
+];
+
+/// Elements that start a new block; everything else is rendered inline
+const BLOCK_TAGS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "body",
+    "dd",
+    "details",
+    "dialog",
+    "div",
+    "dl",
+    "dt",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "html",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "summary",
+    "table",
+    "ul",
+];
+
+/// Convert an HTML document or fragment to Markdown
+pub fn to_markdown(html: &str, base: Option<&Url>) -> String {
+    let document = Html::parse_document(html);
+    let converter = Converter {
+        base,
+        depth: Cell::new(0),
+    };
+    converter.blocks(document.root_element()).join("\n\n")
+}
+
+struct Converter<'a> {
+    base: Option<&'a Url>,
+    /// Current element nesting, bounded by `MAX_DEPTH`
+    depth: Cell<usize>,
+}
+
+impl Converter<'_> {
+    /// Run `render` one nesting level deeper, or None once `MAX_DEPTH` is reached
+    fn nested<T>(&self, render: impl FnOnce() -> T) -> Option<T> {
+        let depth = self.depth.get();
+        if depth >= MAX_DEPTH {
+            return None;
+        }
+        self.depth.set(depth + 1);
+        let result = render();
+        self.depth.set(depth);
+        Some(result)
+    }
+
+    /// Render the children of `el` as a list of Markdown blocks
+    fn blocks(&self, el: ElementRef) -> Vec<String> {
+        self.nested(|| self.blocks_within(el)).unwrap_or_else(|| {
+            let mut blocks = Vec::new();
+            push_paragraph(&mut blocks, &mut flat_text(el));
+            blocks
+        })
+    }
+
+    fn blocks_within(&self, el: ElementRef) -> Vec<String> {
+        let mut blocks = Vec::new();
+        let mut paragraph = String::new();
+
+        for child in el.children() {
+            match child.value() {
+                Node::Text(text) => paragraph.push_str(&collapse(text)),
+                Node::Element(_) => {
+                    let Some(child) = ElementRef::wrap(child) else {
+                        continue;
+                    };
+                    let name = child.value().name();
+                    if SKIPPED_TAGS.contains(&name) {
+                        continue;
+                    }
+                    if BLOCK_TAGS.contains(&name) {
+                        push_paragraph(&mut blocks, &mut paragraph);
+                        blocks.extend(self.block(child));
+                    } else {
+                        paragraph.push_str(&self.inline(child));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        push_paragraph(&mut blocks, &mut paragraph);
+        blocks
+    }
+
+    fn block(&self, el: ElementRef) -> Vec<String> {
+        let name = el.value().name();
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = name[1..].parse().unwrap_or(1);
+                let text = single_line(&self.inline_children(el));
+                if text.is_empty() {
+                    return Vec::new();
+                }
+                vec![format!("{} {}", "#".repeat(level), text)]
+            }
+            "p" | "dd" | "summary" | "figcaption" => {
+                let mut blocks = Vec::new();
+                let mut paragraph = self.inline_children(el);
+                push_paragraph(&mut blocks, &mut paragraph);
+                blocks
+            }
+            "dt" => {
+                let text = single_line(&self.inline_children(el));
+                if text.is_empty() {
+                    return Vec::new();
+                }
+                vec![format!("**{}**", text)]
+            }
+            "pre" => vec![code_block(el)],
+            "blockquote" => {
+                let inner = self.blocks(el).join("\n\n");
+                if inner.is_empty() {
+                    return Vec::new();
+                }
+                let quoted = inner
+                    .lines()
+                    .map(|line| {
+                        if line.is_empty() {
+                            ">".to_string()
+                        } else {
+                            format!("> {}", line)
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                vec![quoted]
+            }
+            "ul" | "ol" => self.list(el).into_iter().collect(),
+            "table" => self.table(el).into_iter().collect(),
+            "hr" => vec!["---".to_string()],
+            _ => self.blocks(el),
+        }
+    }
+
+    fn list(&self, el: ElementRef) -> Option<String> {
+        let ordered = el.value().name() == "ol";
+        let start: usize = el
+            .value()
+            .attr("start")
+            .and_then(|s| s.trim().parse().ok())
+            .unwrap_or(1);
+
+        let items: Vec<String> = el
+            .children()
+            .filter_map(ElementRef::wrap)
+            .filter(|child| child.value().name() == "li")
+            .enumerate()
+            .filter_map(|(i, li)| {
+                let content = self.blocks(li).join("\n");
+                if content.is_empty() {
+                    return None;
+                }
+                let marker = if ordered {
+                    format!("{}. ", start + i)
+                } else {
+                    "- ".to_string()
+                };
+                // Continuation lines line up under the item text
+                let indent = " ".repeat(marker.len());
+                let mut lines = content.lines();
+                let mut item = format!("{}{}", marker, lines.next().unwrap_or_default());
+                for line in lines {
+                    item.push('\n');
+                    if !line.is_empty() {
+                        item.push_str(&indent);
+                        item.push_str(line);
+                    }
+                }
+                Some(item)
+            })
+            .collect();
+
+        (!items.is_empty()).then(|| items.join("\n"))
+    }
+
+    fn table(&self, el: ElementRef) -> Option<String> {
+        let rows: Vec<Vec<String>> = el
+            .descendants()
+            .filter_map(ElementRef::wrap)
+            .filter(|row| row.value().name() == "tr")
+            // Rows of nested tables belong to the nested table
+            .filter(|row| {
+                row.ancestors()
+                    .filter_map(ElementRef::wrap)
+                    .find(|a| a.value().name() == "table")
+                    .is_some_and(|table| table.id() == el.id())
+            })
+            .map(|row| {
+                row.children()
+                    .filter_map(ElementRef::wrap)
+                    .filter(|cell| matches!(cell.value().name(), "td" | "th"))
+                    .map(|cell| single_line(&self.inline_children(cell)).replace('|', "\\|"))
+                    .collect()
+            })
+            .filter(|cells: &Vec<String>| !cells.is_empty())
+            .collect();
+
+        let columns = rows.iter().map(Vec::len).max()?;
+        let render = |cells: &[String]| {
+            let mut padded = cells.to_vec();
+            padded.resize(columns, String::new());
+            format!("| {} |", padded.join(" | "))
+        };
+
+        // Markdown tables need a header row; the first row serves as one
+        let mut lines = vec![render(&rows[0])];
+        lines.push(format!("|{}", " --- |".repeat(columns)));
+        lines.extend(rows[1..].iter().map(|row| render(row)));
+        Some(lines.join("\n"))
+    }
+
+    fn inline_children(&self, el: ElementRef) -> String {
+        self.nested(|| self.inline_within(el))
+            .unwrap_or_else(|| flat_text(el))
+    }
+
+    fn inline_within(&self, el: ElementRef) -> String {
+        let mut out = String::new();
+        for child in el.children() {
+            match child.value() {
+                Node::Text(text) => out.push_str(&collapse(text)),
+                Node::Element(_) => {
+                    if let Some(child) = ElementRef::wrap(child) {
+                        out.push_str(&self.inline(child));
+                    }
+                }
+                _ => {}
+            }
+        }
+        out
+    }
+
+    fn inline(&self, el: ElementRef) -> String {
+        let name = el.value().name();
+        if SKIPPED_TAGS.contains(&name) {
+            return String::new();
+        }
+
+        match name {
+            "br" => "\n".to_string(),
+            "strong" | "b" => wrap(&self.inline_children(el), "**"),
+            "em" | "i" | "cite" => wrap(&self.inline_children(el), "*"),
+            "del" | "s" | "strike" => wrap(&self.inline_children(el), "~~"),
+            "code" | "kbd" | "samp" | "tt" => {
+                let code = single_line(&el.text().collect::<String>());
+                if code.is_empty() {
+                    return String::new();
+                }
+                let fence = "`".repeat(longest_run(&code, '`') + 1);
+                let pad = if code.starts_with('`') || code.ends_with('`') {
+                    " "
+                } else {
+                    ""
+                };
+                format!("{fence}{pad}{code}{pad}{fence}")
+            }
+            "a" => {
+                let text = self.inline_children(el);
+                let label = single_line(&text);
+                match el.value().attr("href").and_then(|href| self.resolve(href)) {
+                    Some(href) if !label.is_empty() => {
+                        let (lead, trail) = outer_spaces(&text);
+                        format!("{lead}[{label}]({href}){trail}")
+                    }
+                    _ => text,
+                }
+            }
+            "img" => {
+                let alt = single_line(el.value().attr("alt").unwrap_or_default());
+                match el.value().attr("src").and_then(|src| self.resolve(src)) {
+                    Some(src) => format!("![{}]({})", alt, src),
+                    None => alt,
+                }
+            }
+            // Block content nested in inline markup (e.g. a <div> inside <a>)
+            _ if BLOCK_TAGS.contains(&name) => format!(" {} ", self.inline_children(el)),
+            _ => self.inline_children(el),
+        }
+    }
+
+    /// Absolute link target, or None for script links and unparsable values
+    fn resolve(&self, href: &str) -> Option<String> {
+        let href = href.trim();
+        if href.is_empty() || href.to_ascii_lowercase().starts_with("javascript:") {
+            return None;
+        }
+        match self.base {
+            Some(base) => base.join(href).ok().map(String::from),
+            None => Some(href.to_string()),
+        }
+    }
+}
+
+/// Fenced code block, with the language taken from a `language-*` or `lang-*` class
+fn code_block(pre: ElementRef) -> String {
+    let language = std::iter::once(pre)
+        .chain(pre.descendants().filter_map(ElementRef::wrap))
+        .filter_map(|el| el.value().attr("class"))
+        .flat_map(str::split_whitespace)
+        .find_map(|class| {
+            class
+                .strip_prefix("language-")
+                .or_else(|| class.strip_prefix("lang-"))
+        })
+        .unwrap_or_default()
+        .to_string();
+
+    let code = pre.text().collect::<String>();
+    let code = code.trim_matches('\n').trim_end();
+    let fence = "`".repeat(longest_run(code, '`').max(2) + 1);
+    format!("{fence}{language}\n{code}\n{fence}")
+}
+
+/// Text of every rendered descendant, collapsed, without recursing
+fn flat_text(el: ElementRef) -> String {
+    let text: String = el
+        .descendants()
+        .filter(|node| {
+            let skipped = node
+                .parent()
+                .and_then(ElementRef::wrap)
+                .is_some_and(|parent| SKIPPED_TAGS.contains(&parent.value().name()));
+            !skipped
+        })
+        .filter_map(|node| node.value().as_text().map(|text| &**text))
+        .collect();
+    collapse(&text)
+}
+
+/// Whitespace runs collapsed to one space, as a browser renders text
+fn collapse(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_space = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !in_space {
+                out.push(' ');
+            }
+            in_space = true;
+        } else {
+            out.push(c);
+            in_space = false;
+        }
+    }
+    out
+}
+
+fn single_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Emphasis markers must hug the text, so surrounding spaces move outside
+fn wrap(text: &str, marker: &str) -> String {
+    let inner = single_line(text);
+    if inner.is_empty() {
+        return text.to_string();
+    }
+    let (lead, trail) = outer_spaces(text);
+    format!("{lead}{marker}{inner}{marker}{trail}")
+}
+
+fn outer_spaces(text: &str) -> (&'static str, &'static str) {
+    let lead = if text.starts_with(char::is_whitespace) {
+        " "
+    } else {
+        ""
+    };
+    let trail = if text.ends_with(char::is_whitespace) {
+        " "
+    } else {
+        ""
+    };
+    (lead, trail)
+}
+
+fn longest_run(text: &str, c: char) -> usize {
+    let mut longest = 0;
+    let mut current = 0;
+    for ch in text.chars() {
+        if ch == c {
+            current += 1;
+            longest = longest.max(current);
+        } else {
+            current = 0;
+        }
+    }
+    longest
+}
+
+/// Flush accumulated inline content as a paragraph (line breaks from <br> kept)
+fn push_paragraph(blocks: &mut Vec<String>, paragraph: &mut String) {
+    let text = paragraph
+        .lines()
+        .map(single_line)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    if !text.is_empty() {
+        blocks.push(text);
+    }
+    paragraph.clear();
+}
@@ -0,0 +1,165 @@
+//! Page metadata extraction: `<title>`, meta tags, OpenGraph, Twitter cards and JSON-LD
+
+use crate::models::PageMetadata;
+use reqwest::Url;
+use scraper::{ElementRef, Html, Selector};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// Collect the metadata declared in an HTML page.
+///
+/// Each field takes the first source that provides it, preferring explicit
+/// structured data (OpenGraph, JSON-LD) over generic tags. URLs are resolved
+/// against the page URL.
+pub fn extract(html: &str, url: &str) -> PageMetadata {
+    let document = Html::parse_document(html);
+    let base = Url::parse(url).ok();
+    let resolve = |href: &str| match &base {
+        Some(base) => base.join(href.trim()).ok().map(String::from),
+        None => Some(href.trim().to_string()),
+    };
+
+    let mut open_graph = BTreeMap::new();
+    let mut twitter = BTreeMap::new();
+    let mut named = HashMap::new();
+
+    let meta_sel = Selector::parse("meta[content]").unwrap();
+    for meta in document.select(&meta_sel) {
+        let content = clean(meta.value().attr("content").unwrap_or_default());
+        if content.is_empty() {
+            continue;
+        }
+        // OpenGraph uses `property`, but `name` is common in the wild (and vice versa for Twitter)
+        for key in ["property", "name", "itemprop", "http-equiv"]
+            .iter()
+            .filter_map(|attr| meta.value().attr(attr))
+        {
+            let key = key.trim().to_ascii_lowercase();
+            if let Some(og) = key.strip_prefix("og:") {
+                open_graph
+                    .entry(og.to_string())
+                    .or_insert_with(|| content.clone());
+            } else if let Some(card) = key.strip_prefix("twitter:") {
+                twitter
+                    .entry(card.to_string())
+                    .or_insert_with(|| content.clone());
+            }
+            named.entry(key).or_insert_with(|| content.clone());
+        }
+    }
+    let meta = |keys: &[&str]| keys.iter().find_map(|key| named.get(*key).cloned());
+
+    let json_ld = json_ld_blocks(&document);
+    let ld = |key: &str| json_ld_items(&json_ld).find_map(|item| ld_text(item.get(key)?));
+
+    let title_sel = Selector::parse("title").unwrap();
+    let title_tag = document
+        .select(&title_sel)
+        .next()
+        .map(|el| clean(&el.text().collect::<String>()))
+        .filter(|t| !t.is_empty());
+
+    let canonical_sel = Selector::parse("link[rel][href]").unwrap();
+    let canonical = document
+        .select(&canonical_sel)
+        .find(|link| has_rel(*link, "canonical"))
+        .and_then(|link| resolve(link.value().attr("href")?));
+
+    let html_sel = Selector::parse("html[lang]").unwrap();
+    let language = document
+        .select(&html_sel)
+        .next()
+        .and_then(|el| el.value().attr("lang"))
+        .map(clean)
+        .filter(|lang| !lang.is_empty())
+        .or_else(|| meta(&["content-language", "language", "og:locale"]));
+
+    PageMetadata {
+        title: open_graph
+            .get("title")
+            .cloned()
+            .or_else(|| ld("headline"))
+            .or(title_tag)
+            .or_else(|| meta(&["twitter:title"])),
+        description: meta(&["description", "og:description", "twitter:description"])
+            .or_else(|| ld("description")),
+        canonical_url: canonical.or_else(|| meta(&["og:url"]).and_then(|u| resolve(&u))),
+        author: meta(&["author", "article:author", "dc.creator", "parsely-author"])
+            .filter(|author| !author.starts_with("http"))
+            .or_else(|| ld("author"))
+            .or_else(|| meta(&["twitter:creator"])),
+        published: meta(&[
+            "article:published_time",
+            "datepublished",
+            "date",
+            "pubdate",
+            "publish-date",
+            "dc.date",
+            "dc.date.issued",
+        ])
+        .or_else(|| ld("datePublished")),
+        modified: meta(&["article:modified_time", "og:updated_time", "datemodified"])
+            .or_else(|| ld("dateModified")),
+        language,
+        site_name: meta(&["og:site_name", "application-name"])
+            .or_else(|| json_ld_items(&json_ld).find_map(|item| ld_text(item.get("publisher")?))),
+        image: meta(&["og:image", "twitter:image"]).and_then(|u| resolve(&u)),
+        page_count: None,
+        open_graph,
+        twitter,
+        json_ld,
+    }
+}
+
+fn has_rel(link: ElementRef, rel: &str) -> bool {
+    link.value()
+        .attr("rel")
+        .is_some_and(|rels| rels.split_whitespace().any(|r| r.eq_ignore_ascii_case(rel)))
+}
+
+/// Valid `application/ld+json` blocks; invalid JSON is skipped
+fn json_ld_blocks(document: &Html) -> Vec<Value> {
+    let selector = Selector::parse("script[type]").unwrap();
+    document
+        .select(&selector)
+        .filter(|script| {
+            script
+                .value()
+                .attr("type")
+                .is_some_and(|t| t.trim().eq_ignore_ascii_case("application/ld+json"))
+        })
+        .filter_map(|script| serde_json::from_str(script.text().collect::<String>().trim()).ok())
+        .collect()
+}
+
+/// Every JSON-LD object, looking inside top-level arrays and `@graph`
+fn json_ld_items(blocks: &[Value]) -> impl Iterator<Item = &Value> {
+    blocks.iter().flat_map(|block| {
+        let items: Vec<&Value> = match block {
+            Value::Array(items) => items.iter().collect(),
+            Value::Object(map) => match map.get("@graph") {
+                Some(Value::Array(graph)) => graph.iter().collect(),
+                _ => vec![block],
+            },
+            _ => Vec::new(),
+        };
+        items
+    })
+}
+
+/// Text of a JSON-LD value: a string, an object's `name`, or a list of either
+fn ld_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(clean(s)).filter(|s| !s.is_empty()),
+        Value::Object(map) => map.get("name").and_then(ld_text),
+        Value::Array(items) => {
+            let names: Vec<String> = items.iter().filter_map(ld_text).collect();
+            (!names.is_empty()).then(|| names.join(", "))
+        }
+        _ => None,
+    }
+}
+
+fn clean(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
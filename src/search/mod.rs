@@ -1,181 +1,500 @@
 //! DuckDuckGo web search implementation
 
-use crate::models::{SearchParams, SearchResult, SearchResponse};
+mod body;
+mod document;
+mod encoding;
+mod feed;
+mod jsonpath;
+mod links;
+mod markdown;
+mod metadata;
+mod pdf;
+mod readability;
+mod proxy;
+mod redirect;
+mod robots;
+
+use crate::cache::{
+    CacheConfig, CacheStats, CachedPage, DiskCache, DiskCacheConfig, SingleFlight, TtlCache,
+    canonical_url, unix_now,
+};
+use crate::config::Config;
+use crate::domains::{DomainBlocked, DomainPolicy};
+use crate::models::{
+    ContentChunk, ContentFormat, FetchOptions, FetchResponse, LinkOptions, LinksResponse,
+    PageMetadata, Redirect, SelectorMatches, SelectorOptions, SelectorResponse, RetryInfo, SearchParams, SearchResult, SearchResponse};
+use crate::ratelimit::{HostRateLimiter, RateLedger, RateLimitConfig, RateLimiter};
+use crate::retry::RetryPolicy;
+use crate::ssrf::{GuardedResolver, SsrfError, SsrfPolicy};
+use document::DocumentKind;
 use anyhow::Result;
+use reqwest::StatusCode;
+use reqwest::header::{
+    CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, LOCATION,
+};
 use scraper::{Html, Selector};
 use std::sync::Arc;
-use std::time::{Duration, Instant};
-use tokio::sync::Mutex;
-
-/// Rate limiter to avoid getting blocked
-pub struct RateLimiter {
-    requests: Arc<Mutex<Vec<Instant>>>,
-    max_requests_per_minute: usize,
-    min_delay: Duration,
-    last_request: Arc<Mutex<Option<Instant>>>,
-}
+use std::time::Duration;
 
-impl RateLimiter {
-    pub fn new(max_requests_per_minute: usize, min_delay_secs: u64) -> Self {
-        Self {
-            requests: Arc::new(Mutex::new(Vec::new())),
-            max_requests_per_minute,
-            min_delay: Duration::from_secs(min_delay_secs),
-            last_request: Arc::new(Mutex::new(None)),
-        }
-    }
+/// Browser User-Agent sent unless configured otherwise
+pub const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
 
-    pub async fn acquire(&self) {
-        let wait_time = {
-            let mut requests = self.requests.lock().await;
-            let mut last_request = self.last_request.lock().await;
-            let now = Instant::now();
+/// Largest DuckDuckGo results page read (default)
+pub const DEFAULT_MAX_SEARCH_BODY: u64 = 2 * 1024 * 1024;
 
-            // Check minimum delay since last request
-            if let Some(last) = *last_request {
-                let elapsed = now.duration_since(last);
-                if elapsed < self.min_delay {
-                    Some(self.min_delay.saturating_sub(elapsed))
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
-        };
+/// Largest fetched page or document read (default)
+pub const DEFAULT_MAX_FETCH_BODY: u64 = 10 * 1024 * 1024;
 
-        // Sleep for minimum delay if needed
-        if let Some(wait) = wait_time {
-            if wait > Duration::ZERO {
-                tokio::time::sleep(wait).await;
-            }
-        }
+pub use body::BodyError;
+pub use proxy::ProxyConfig;
+pub use redirect::{RedirectError, RedirectPolicy};
+pub use robots::{RobotsConfig, RobotsError, RobotsTxt};
+use robots::RobotsChecker;
 
-        // Now check per-minute limit
-        let wait_time = {
-            let mut requests = self.requests.lock().await;
-            let now = Instant::now();
+/// DuckDuckGo web scraper
+pub struct DuckDuckGoScraper {
+    client: reqwest::Client,
+    fetch_client: reqwest::Client,
+    user_agent: String,
+    timeout: Duration,
+    search_proxy: Option<ProxyConfig>,
+    fetch_proxy: Option<ProxyConfig>,
+    ssrf: Arc<SsrfPolicy>,
+    domains: Arc<DomainPolicy>,
+    redirect_policy: RedirectPolicy,
+    robots: Option<RobotsChecker>,
+    rate_limits: HostRateLimiter,
+    search_limits: RateLimitConfig,
+    host_limits: RateLimitConfig,
+    rate_ledger: Option<RateLedger>,
+    search_retry: RetryPolicy,
+    fetch_retry: RetryPolicy,
+    max_search_body: u64,
+    max_fetch_body: u64,
+    default_max_length: usize,
+    search_cache: TtlCache<String, Vec<SearchResult>>,
+    fetch_cache: TtlCache<String, CachedPage>,
+    disk_cache: Option<DiskCache>,
+    inflight_searches: SingleFlight<String, (Vec<SearchResult>, Option<RetryInfo>), SharedError>,
+    inflight_fetches: SingleFlight<String, FetchedPage, SharedError>,
+}
 
-            // Remove requests older than 1 minute
-            requests.retain(|req| now.duration_since(*req) < Duration::from_secs(60));
+/// A failed upstream call as handed to every coalesced caller.
+///
+/// Policy refusals keep their type so each caller can still downcast them;
+/// other errors are shared and, once shared, reach followers by message only.
+#[derive(Debug, Clone)]
+enum SharedError {
+    Ssrf(SsrfError),
+    Domain(DomainBlocked),
+    Robots(RobotsError),
+    Other(Arc<anyhow::Error>),
+}
 
-            if requests.len() >= self.max_requests_per_minute {
-                // Calculate wait time
-                if let Some(oldest) = requests.first() {
-                    let elapsed = now.duration_since(*oldest);
-                    Some(Duration::from_secs(60).saturating_sub(elapsed))
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
+impl From<anyhow::Error> for SharedError {
+    fn from(e: anyhow::Error) -> Self {
+        let e = match e.downcast::<SsrfError>() {
+            Ok(e) => return Self::Ssrf(e),
+            Err(e) => e,
         };
-
-        // Sleep outside the lock if needed
-        if let Some(wait) = wait_time {
-            if wait > Duration::ZERO {
-                tokio::time::sleep(wait).await;
-            }
+        let e = match e.downcast::<DomainBlocked>() {
+            Ok(e) => return Self::Domain(e),
+            Err(e) => e,
+        };
+        match e.downcast::<RobotsError>() {
+            Ok(e) => Self::Robots(e),
+            Err(e) => Self::Other(Arc::new(e)),
         }
+    }
+}
 
-        // Update last request time and add the new request
-        let mut requests = self.requests.lock().await;
-        let mut last_request = self.last_request.lock().await;
-        *last_request = Some(Instant::now());
-        requests.push(Instant::now());
+impl From<SharedError> for anyhow::Error {
+    fn from(e: SharedError) -> Self {
+        match e {
+            SharedError::Ssrf(e) => e.into(),
+            SharedError::Domain(e) => e.into(),
+            SharedError::Robots(e) => e.into(),
+            // Only the leader of an uncontended call still holds the original
+            SharedError::Other(e) => Arc::try_unwrap(e).unwrap_or_else(|e| anyhow::anyhow!("{}", e)),
+        }
     }
 }
 
-/// DuckDuckGo web scraper
-pub struct DuckDuckGoScraper {
-    client: reqwest::Client,
-    rate_limiter: RateLimiter,
+/// Outcome of one upstream page request, shared by coalesced callers
+#[derive(Clone)]
+struct FetchedPage {
+    status: StatusCode,
+    page: Option<CachedPage>,
+    retry: RetryInfo,
+    /// Redirects followed, also recorded on `page` when there is one
+    redirects: Vec<Redirect>,
 }
 
 impl DuckDuckGoScraper {
     /// Create a new scraper instance
     pub fn new() -> Self {
-        let client = reqwest::Client::builder()
-            .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
-            .timeout(Duration::from_secs(30))
-            .build()
-            .expect("Failed to create HTTP client");
+        let search_limits = RateLimitConfig::new(20, Duration::from_secs(3)); // 20 req/min, 3 sec min delay
+        let host_limits = RateLimitConfig::new(20, Duration::from_secs(1)).with_burst(5); // per fetched host
+
+        let mut scraper = Self {
+            client: reqwest::Client::new(),
+            fetch_client: reqwest::Client::new(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            timeout: Duration::from_secs(30),
+            search_proxy: None,
+            fetch_proxy: None,
+            ssrf: Arc::new(SsrfPolicy::new()),
+            domains: Arc::new(DomainPolicy::new()),
+            redirect_policy: RedirectPolicy::default(),
+            robots: None,
+            rate_limits: HostRateLimiter::new(search_limits.clone(), host_limits.clone()),
+            search_limits,
+            host_limits,
+            rate_ledger: None,
+            search_retry: RetryPolicy::default(),
+            fetch_retry: RetryPolicy::default(),
+            max_search_body: DEFAULT_MAX_SEARCH_BODY,
+            max_fetch_body: DEFAULT_MAX_FETCH_BODY,
+            default_max_length: FetchOptions::DEFAULT_MAX_LENGTH,
+            search_cache: TtlCache::new(&CacheConfig::new(200, Duration::from_secs(600))),
+            fetch_cache: TtlCache::new(&CacheConfig::new(100, Duration::from_secs(900))),
+            disk_cache: None,
+            inflight_searches: SingleFlight::new(),
+            inflight_fetches: SingleFlight::new(),
+        };
+        scraper.rebuild_clients();
+        scraper
+    }
+
+    /// Create a scraper with every setting taken from a validated `Config`
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let (http, search, fetch, cache) = (&config.http, &config.search, &config.fetch, &config.cache);
+        let (search_proxy, fetch_proxy) = config.proxies()?;
+        let mut scraper = Self::new()
+            .with_http_settings(&http.user_agent, Duration::from_secs(http.timeout_secs))
+            .with_rate_limits(
+                RateLimitConfig::new(search.requests_per_minute, Duration::from_millis(search.min_delay_ms)),
+                RateLimitConfig::new(fetch.requests_per_minute, Duration::from_millis(fetch.min_delay_ms))
+                    .with_burst(fetch.burst),
+            )
+            .with_retry_policies(config.search_retry(), config.fetch_retry())
+            .with_max_body_sizes(search.max_body_bytes, fetch.max_body_bytes)
+            .with_default_max_length(fetch.default_max_length)
+            .with_redirect_policy(RedirectPolicy {
+                max_hops: fetch.max_redirects,
+                allow_downgrade: fetch.allow_https_downgrade,
+            })
+            .with_cache(
+                CacheConfig::new(cache.search_entries, Duration::from_secs(cache.search_ttl_secs)),
+                CacheConfig::new(cache.fetch_entries, Duration::from_secs(cache.fetch_ttl_secs)),
+            )
+            .with_proxies(search_proxy, fetch_proxy)
+            .with_ssrf_policy(config.ssrf_policy()?)
+            .with_domain_policy(config.domain_policy()?);
+
+        if let Some(path) = &search.rate_ledger {
+            scraper = scraper.with_rate_ledger(RateLedger::new(path));
+        }
+        if let Some(disk) = config.disk_cache() {
+            scraper = scraper.with_disk_cache(disk);
+        }
+        let robots = &config.robots;
+        if robots.enabled {
+            scraper = scraper.with_robots(RobotsConfig {
+                user_agent: robots.user_agent.clone(),
+                cache_ttl: Duration::from_secs(robots.cache_ttl_secs),
+                max_crawl_wait: Duration::from_secs(robots.max_crawl_wait_secs),
+            });
+        }
+        Ok(scraper)
+    }
+
+    /// HTTP client whose DNS answers are checked against the SSRF policy
+    fn build_client(&self, redirect: reqwest::redirect::Policy, proxy: Option<&ProxyConfig>) -> reqwest::Client {
+        let builder = reqwest::Client::builder()
+            .user_agent(&self.user_agent)
+            .timeout(self.timeout)
+            .redirect(redirect);
+
+        // The proxy server may sit on a private address; destinations are still
+        // checked before each request, since the proxy resolves them itself
+        let builder = match proxy {
+            Some(proxy) => builder
+                .proxy(proxy.to_reqwest())
+                .dns_resolver(Arc::new(GuardedResolver::new(Arc::new(self.ssrf.trusting_host(proxy.host()))))),
+            None => builder.dns_resolver(Arc::new(GuardedResolver::new(self.ssrf.clone()))),
+        };
+        builder.build().expect("Failed to create HTTP client")
+    }
+
+    /// Searches let reqwest follow redirects under the policy; page fetches
+    /// follow them by hand so the chain can be reported
+    fn rebuild_clients(&mut self) {
+        let redirect = || self.redirect_policy.for_client(&self.ssrf, &self.domains);
+        self.client = self.build_client(redirect(), self.search_proxy.as_ref());
+        self.fetch_client = self.build_client(reqwest::redirect::Policy::none(), self.fetch_proxy.as_ref());
+        // robots.txt is fetch traffic, but its redirects are followed automatically
+        let robots_client = self
+            .robots
+            .is_some()
+            .then(|| self.build_client(redirect(), self.fetch_proxy.as_ref()));
+        if let (Some(robots), Some(client)) = (&mut self.robots, robots_client) {
+            robots.set_client(client);
+        }
+    }
+
+    /// Send searches and page fetches through proxies (`None` connects directly)
+    pub fn with_proxies(mut self, search: Option<ProxyConfig>, fetch: Option<ProxyConfig>) -> Self {
+        self.search_proxy = search;
+        self.fetch_proxy = fetch;
+        self.rebuild_clients();
+        self
+    }
+
+    /// User-Agent header and overall timeout for every request
+    pub fn with_http_settings(mut self, user_agent: &str, timeout: Duration) -> Self {
+        self.user_agent = user_agent.to_string();
+        self.timeout = timeout;
+        self.rebuild_clients();
+        self
+    }
+
+    /// Allow page fetches to reach restricted (private, loopback, ...) destinations on the allowlist
+    pub fn with_ssrf_policy(mut self, policy: SsrfPolicy) -> Self {
+        self.ssrf = Arc::new(policy);
+        self.rebuild_clients();
+        self
+    }
+
+    /// Hide search results from, and refuse fetches of, domains the policy blocks
+    pub fn with_domain_policy(mut self, policy: DomainPolicy) -> Self {
+        self.domains = Arc::new(policy);
+        self.rebuild_clients();
+        self
+    }
+
+    /// Limit how many redirects are followed and whether https may redirect to http
+    pub fn with_redirect_policy(mut self, policy: RedirectPolicy) -> Self {
+        self.redirect_policy = policy;
+        self.rebuild_clients();
+        self
+    }
+
+    /// Honour each host's robots.txt (Allow/Disallow and Crawl-delay) for page fetches
+    pub fn with_robots(mut self, config: RobotsConfig) -> Self {
+        self.robots = Some(RobotsChecker::new(config));
+        self.rebuild_clients();
+        self
+    }
+
+    /// Use separate cache limits for search results and fetched pages
+    pub fn with_cache(mut self, search: CacheConfig, fetch: CacheConfig) -> Self {
+        self.search_cache = TtlCache::new(&search);
+        self.fetch_cache = TtlCache::new(&fetch);
+        self
+    }
+
+    /// Persist pages and search results on disk so they outlive this process
+    pub fn with_disk_cache(mut self, config: DiskCacheConfig) -> Self {
+        self.disk_cache = Some(DiskCache::new(config));
+        self
+    }
+
+    /// Hit/miss counters for the search and fetch caches
+    pub fn cache_stats(&self) -> (CacheStats, CacheStats) {
+        (self.search_cache.stats(), self.fetch_cache.stats())
+    }
+
+    /// Use separate retry policies for `web_search` and `fetch_content`
+    pub fn with_retry_policies(mut self, search: RetryPolicy, fetch: RetryPolicy) -> Self {
+        self.search_retry = search;
+        self.fetch_retry = fetch;
+        self
+    }
 
-        Self {
-            client,
-            rate_limiter: RateLimiter::new(20, 3), // 20 req/min, 3 sec min delay
+    /// Largest response bodies read for searches and for fetched pages, in bytes
+    pub fn with_max_body_sizes(mut self, search: u64, fetch: u64) -> Self {
+        self.max_search_body = search;
+        self.max_fetch_body = fetch;
+        self
+    }
+
+    /// Content length returned when a fetch does not ask for one
+    pub fn with_default_max_length(mut self, max_length: usize) -> Self {
+        self.default_max_length = max_length.max(1);
+        self
+    }
+
+    pub fn default_max_length(&self) -> usize {
+        self.default_max_length
+    }
+
+    /// Budget shared by all DuckDuckGo searches
+    pub fn search_limits(&self) -> &RateLimitConfig {
+        &self.search_limits
+    }
+
+    /// Budget applied to each fetched host separately
+    pub fn host_limits(&self) -> &RateLimitConfig {
+        &self.host_limits
+    }
+
+    /// Use separate budgets for DuckDuckGo searches and for each fetched host
+    pub fn with_rate_limits(mut self, search: RateLimitConfig, per_host: RateLimitConfig) -> Self {
+        self.search_limits = search;
+        self.host_limits = per_host;
+        self.rebuild_rate_limits();
+        self
+    }
+
+    /// Share the search budget with every local server process through an on-disk ledger
+    pub fn with_rate_ledger(mut self, ledger: RateLedger) -> Self {
+        self.rate_ledger = Some(ledger);
+        self.rebuild_rate_limits();
+        self
+    }
+
+    fn rebuild_rate_limits(&mut self) {
+        let mut search = RateLimiter::with_config(&self.search_limits);
+        if let Some(ledger) = &self.rate_ledger {
+            search = search.with_ledger(ledger.clone(), "search");
         }
+        self.rate_limits = HostRateLimiter::with_search_limiter(search, self.host_limits.clone());
     }
 
     /// Perform web search
     pub async fn search(&self, params: &SearchParams) -> Result<SearchResponse> {
-        // Apply rate limiting
-        self.rate_limiter.acquire().await;
-        self.search_basic(params).await
+        let key = params.cache_key();
+        if !params.no_cache {
+            if let Some(results) = self.search_cache.get(&key) {
+                return Ok(self.paginate(params, results, None));
+            }
+            if let Some(results) = self.disk_cache.as_ref().and_then(|d| d.load_search(&key)) {
+                self.search_cache.insert(key, results.clone());
+                return Ok(self.paginate(params, results, None));
+            }
+        }
+
+        // Concurrent identical queries share one upstream request and rate-limit token
+        let (results, retry) = self
+            .inflight_searches
+            .run(key, || async {
+                self.search_basic(params).await.map_err(SharedError::from)
+            })
+            .await?;
+
+        Ok(self.paginate(params, results, retry))
     }
 
-    /// Basic single-page search using POST (more reliable than GET)
-    async fn search_basic(&self, params: &SearchParams) -> Result<SearchResponse> {
+    /// Basic single-page search using POST (more reliable than GET).
+    /// Returns every result on the page; callers paginate.
+    async fn search_basic(
+        &self,
+        params: &SearchParams,
+    ) -> Result<(Vec<SearchResult>, Option<RetryInfo>)> {
         // Use POST request like the Python version
         let form_data = &[("q", params.query.as_str()), ("b", ""), ("kl", "")];
 
-        let html = self.fetch_page_post(form_data).await?;
+        let (status, html, retry) = self.fetch_page_post(form_data).await?;
 
         // Check for CAPTCHA/challenge
-        if html.contains("anomaly-modal") || html.contains("challenge-submit") {
-            return Ok(SearchResponse {
-                query: params.query.clone(),
-                results: vec![],
-                total_results: 0,
-                returned: 0,
-                offset: params.offset,
-            });
+        let captcha = html.contains("anomaly-modal") || html.contains("challenge-submit");
+        if captcha || was_throttled(status, &retry) {
+            self.rate_limits.search().record_throttled();
+        } else if status.is_success() {
+            self.rate_limits.search().record_success();
+        }
+
+        let retry = retry.retried().then_some(retry);
+        if captcha {
+            return Ok((vec![], retry));
         }
 
-        let mut results = self.parse_results(&html);
+        let results = self.parse_results(&html);
+
+        // Empty pages are not cached so a layout change or soft block is not sticky
+        if status.is_success() && !results.is_empty() {
+            if let Some(disk) = &self.disk_cache {
+                disk.store_search(&params.cache_key(), &results);
+            }
+            self.search_cache.insert(params.cache_key(), results.clone());
+        }
+
+        Ok((results, retry))
+    }
+
+    /// Drop results from blocked domains, then apply offset and limit.
+    ///
+    /// Caches hold the unfiltered list so a policy change applies immediately.
+    fn paginate(
+        &self,
+        params: &SearchParams,
+        results: Vec<SearchResult>,
+        retry: Option<RetryInfo>,
+    ) -> SearchResponse {
+        let before = results.len();
+        let results: Vec<_> = results
+            .into_iter()
+            .filter(|result| self.domains.permits(&result.url))
+            .collect();
+        let filtered = before - results.len();
 
         let total = results.len();
         let paginated: Vec<_> = results
-            .drain(..)
+            .into_iter()
             .skip(params.offset)
             .take(params.limit)
             .collect();
 
         let returned = paginated.len();
 
-        Ok(SearchResponse {
+        SearchResponse {
             query: params.query.clone(),
             results: paginated,
             total_results: total,
             returned,
             offset: params.offset,
-        })
+            filtered,
+            retry,
+        }
     }
 
     /// Fetch a single page using POST (more reliable)
-    async fn fetch_page_post(&self, form_data: &[(&str, &str)]) -> Result<String> {
-        let resp = self.client
-            .post("https://html.duckduckgo.com/html/")
-            .form(form_data)
-            .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8")
-            .header("Accept-Language", "en-US,en;q=0.9")
-            .header("DNT", "1")
-            .send()
+    async fn fetch_page_post(
+        &self,
+        form_data: &[(&str, &str)],
+    ) -> Result<(StatusCode, String, RetryInfo)> {
+        // Apply rate limiting to every attempt, retries included
+        let limiter = self.rate_limits.search();
+        let (resp, retry) = self
+            .search_retry
+            .send_paced(|| limiter.acquire(), || {
+                self.client
+                    .post("https://html.duckduckgo.com/html/")
+                    .form(form_data)
+                    .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8")
+                    .header("Accept-Language", "en-US,en;q=0.9")
+                    .header("DNT", "1")
+            })
             .await?;
-        Ok(resp.text().await?)
+        let status = resp.status();
+        let content_type = resp
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let bytes = body::read_limited(resp, self.max_search_body).await?;
+        Ok((status, encoding::decode(&bytes, content_type.as_deref()), retry))
     }
 
     /// Extract real URL from DuckDuckGo redirect URL
     fn extract_real_url(ddg_url: &str) -> Option<String> {
         // DDG uses redirect URLs like: //duckduckgo.com/l/?uddg=<encoded_url>
+        // The HTML endpoint emits them relative (/l/?uddg=...), so decode whatever the prefix
         if ddg_url.contains("uddg=") {
             let url_part = ddg_url.split("uddg=").nth(1)?;
             let encoded = url_part.split('&').next().unwrap_or(url_part);
-            match urlencoding::decode(encoded) {
-                Ok(decoded) => Some(decoded.to_string()),
-                Err(_) => None,
-            }
+            urlencoding::decode(encoded).ok().map(|decoded| decoded.to_string())
         } else if ddg_url.starts_with("//") {
             Some(format!("https:{}", ddg_url))
         } else if ddg_url.starts_with("http://") || ddg_url.starts_with("https://") {
@@ -244,10 +563,24 @@ impl DuckDuckGoScraper {
     /// Format results in LLM-friendly natural language style
     pub fn format_results_for_llm(&self, response: &SearchResponse) -> String {
         if response.results.is_empty() {
-            return format!(
+            let mut output = format!(
                 "No results found for: {}\n\nThis could be due to rate limiting or no matches. Try rephrasing your search.",
                 response.query
             );
+            if response.filtered > 0 {
+                output.push_str(&format!("\n\n{}", filtered_note(response.filtered)));
+            }
+            let limiter = self.rate_limits.search();
+            if limiter.slowdown() > 1.0 {
+                output.push_str(&format!(
+                    "\n\nDuckDuckGo is throttling requests; search rate temporarily reduced to {:.1} requests/minute.",
+                    limiter.effective_rate()
+                ));
+            }
+            if let Some(note) = response.retry.as_ref().and_then(RetryInfo::note) {
+                output.push_str(&format!("\n\n{}", note));
+            }
+            return output;
         }
 
         let mut output = format!("Found {} search results for \"{}\":\n\n", response.returned, response.query);
@@ -262,75 +595,594 @@ impl DuckDuckGoScraper {
             ));
         }
 
+        if response.filtered > 0 {
+            output.push_str(&filtered_note(response.filtered));
+            output.push('\n');
+        }
+
+        if let Some(note) = response.retry.as_ref().and_then(RetryInfo::note) {
+            output.push_str(&note);
+            output.push('\n');
+        }
+
         output
     }
 
     /// Fetch and parse webpage content
     pub async fn fetch_content(&self, url: &str) -> Result<String> {
-        // Apply rate limiting
-        self.rate_limiter.acquire().await;
-
-        let resp = self.client
-            .get(url)
-            .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8")
-            .header("Accept-Language", "en-US,en;q=0.9")
-            .header("DNT", "1")
-            .send()
-            .await?;
+        self.fetch_content_with(url, &FetchOptions::default()).await
+    }
 
-        let status = resp.status();
-        let html = resp.text().await?;
+    /// Fetch and parse webpage content with per-call options
+    pub async fn fetch_content_with(&self, url: &str, options: &FetchOptions) -> Result<String> {
+        Ok(self.fetch_page(url, options).await?.text)
+    }
+
+    /// Fetch a page and return its content together with its metadata
+    pub async fn fetch_page(&self, url: &str, options: &FetchOptions) -> Result<FetchResponse> {
+        // Reject a malformed JSONPath before spending a request on the page
+        if let Some(path) = &options.json_path {
+            document::parse_json_path(path)?;
+        }
+        let fetched = self.load_page(url, options.no_cache).await?;
+
+        let mut response = match &fetched.page {
+            Some(page) => self.render_response(page, url, options)?,
+            None => {
+                let final_url = fetched.redirects.last().map(|r| r.url.clone());
+                let mut text = http_error(fetched.status, final_url.as_deref().unwrap_or(url));
+                if let Some(note) = redirect_note(url, &fetched.redirects) {
+                    text = format!("{}\n\n{}", note, text);
+                }
+                FetchResponse {
+                    url: url.to_string(),
+                    final_url,
+                    redirects: fetched.redirects.clone(),
+                    text,
+                    metadata: None,
+                    chunk: None,
+                    retry: None,
+                }
+            }
+        };
+
+        if let Some(note) = fetched.retry.note() {
+            response.text.push_str("\n\n");
+            response.text.push_str(&note);
+            response.retry = Some(fetched.retry);
+        }
+
+        Ok(response)
+    }
+
+    /// Fetch a page and list its links, filtered by scope and URL pattern
+    pub async fn extract_links(&self, url: &str, options: &LinkOptions) -> Result<LinksResponse> {
+        let fetched = self.load_page(url, options.no_cache).await?;
+        let Some(page) = &fetched.page else {
+            anyhow::bail!("{}", http_error(fetched.status, url));
+        };
+
+        let all = links::extract(&page.body, page.final_url().unwrap_or(url));
+        let total = all.len();
+        let matching: Vec<_> = all
+            .into_iter()
+            .filter(|link| links::matches(link, options))
+            .collect();
+        let filtered = total - matching.len();
+
+        Ok(LinksResponse {
+            url: url.to_string(),
+            links: matching.into_iter().take(options.limit.max(1)).collect(),
+            total,
+            filtered,
+            retry: fetched.retry.retried().then_some(fetched.retry),
+        })
+    }
+
+    /// Format extracted links for LLM consumption
+    pub fn format_links_for_llm(&self, response: &LinksResponse) -> String {
+        let matching = response.total - response.filtered;
+        let mut output = format!("Found {} links on {}", response.total, response.url);
+        let mut notes = Vec::new();
+        if response.filtered > 0 {
+            notes.push(format!("{} filtered out", response.filtered));
+        }
+        if response.links.len() < matching {
+            notes.push(format!("showing first {}", response.links.len()));
+        }
+        if !notes.is_empty() {
+            output.push_str(&format!(" ({})", notes.join(", ")));
+        }
+        output.push_str(":\n\n");
+
+        if response.links.is_empty() {
+            output.push_str("No links match the given filters.");
+        }
+        for (i, link) in response.links.iter().enumerate() {
+            let text = if link.text.is_empty() { "(no text)" } else { &link.text };
+            let mut kind = if link.internal { "internal" } else { "external" }.to_string();
+            if !link.rel.is_empty() {
+                kind.push_str(&format!(", rel={}", link.rel.join(" ")));
+            }
+            output.push_str(&format!("{}. {} ({})\n   {}\n", i + 1, text, kind, link.url));
+        }
+
+        if let Some(note) = response.retry.as_ref().and_then(|r| r.note()) {
+            output.push('\n');
+            output.push_str(&note);
+        }
+
+        output.trim_end().to_string()
+    }
+
+    /// Fetch a page and render the elements matched by each CSS selector
+    pub async fn fetch_selector(
+        &self,
+        url: &str,
+        options: &SelectorOptions,
+    ) -> Result<SelectorResponse> {
+        // Reject bad selectors before spending a request on the page
+        let selectors = parse_selectors(&options.selectors)?;
+
+        let fetched = self.load_page(url, options.no_cache).await?;
+        let Some(page) = &fetched.page else {
+            anyhow::bail!("{}", http_error(fetched.status, url));
+        };
+
+        let (results, truncated) = select_elements(&page.body, page.final_url().unwrap_or(url), &selectors, options);
+
+        Ok(SelectorResponse {
+            url: url.to_string(),
+            results,
+            truncated,
+            retry: fetched.retry.retried().then_some(fetched.retry),
+        })
+    }
+
+    /// Format selector matches for LLM consumption
+    pub fn format_selector_results_for_llm(&self, response: &SelectorResponse) -> String {
+        let mut output = format!("Elements from {}:\n", response.url);
+
+        for result in &response.results {
+            output.push_str(&format!("\n## Selector `{}`: ", result.selector));
+            if result.total == 0 {
+                output.push_str("no matching elements\n");
+                continue;
+            }
+            if result.matches.len() < result.total {
+                output.push_str(&format!(
+                    "{} matches (showing first {})\n",
+                    result.total,
+                    result.matches.len()
+                ));
+            } else if result.total == 1 {
+                output.push_str("1 match\n");
+            } else {
+                output.push_str(&format!("{} matches\n", result.total));
+            }
+            for (i, content) in result.matches.iter().enumerate() {
+                output.push_str(&format!("\n[{}]\n{}\n", i + 1, content));
+            }
+        }
+
+        if response.truncated {
+            output.push_str("\n[Output truncated; narrow the selectors or raise max_length]\n");
+        }
+        if let Some(note) = response.retry.as_ref().and_then(|r| r.note()) {
+            output.push('\n');
+            output.push_str(&note);
+        }
+
+        output.trim_end().to_string()
+    }
+
+    /// The raw page, from the memory cache, a fresh disk copy, or upstream
+    async fn load_page(&self, url: &str, no_cache: bool) -> Result<FetchedPage> {
+        // Blocked domains are refused even when a copy is cached
+        if let Ok(parsed) = reqwest::Url::parse(url) {
+            self.domains.check_url(&parsed)?;
+        }
+
+        let cache_key = canonical_url(url);
+        let cached = |page: CachedPage| FetchedPage {
+            status: StatusCode::OK,
+            redirects: page.redirects.clone(),
+            page: Some(page),
+            retry: RetryInfo::default(),
+        };
 
-        if !status.is_success() {
-            return Ok(format!("HTTP Error: {} - Failed to fetch content from: {}", status.as_u16(), url));
+        if !no_cache && let Some(page) = self.fetch_cache.get(&cache_key) {
+            return Ok(cached(page));
         }
 
-        // Parse and extract readable content
-        let document = Html::parse_document(&html);
+        // A copy on disk is used as-is while fresh and revalidated once stale
+        let stored = self.disk_cache.as_ref().and_then(|disk| {
+            let page = disk.load_page(&cache_key)?;
+            Some((disk.is_fresh(&page), page))
+        });
+        if !no_cache && let Some((true, page)) = &stored {
+            self.fetch_cache.insert(cache_key, page.clone());
+            return Ok(cached(page.clone()));
+        }
+        let stored = stored.map(|(_, page)| page);
+
+        // Concurrent fetches of the same page share one upstream request
+        self.inflight_fetches
+            .run(cache_key.clone(), || async {
+                self.download(url, &cache_key, stored).await.map_err(SharedError::from)
+            })
+            .await
+            .map_err(anyhow::Error::from)
+    }
 
-        // Remove script and style elements
-        let script_selector = Selector::parse("script, style, nav, footer, header, aside").unwrap();
+    /// Request a page (conditionally when a stored copy exists) and cache the result
+    async fn download(
+        &self,
+        url: &str,
+        cache_key: &str,
+        stored: Option<CachedPage>,
+    ) -> Result<FetchedPage> {
+        let mut current = reqwest::Url::parse(url)
+            .map_err(|e| anyhow::anyhow!("Invalid URL '{}': {}", url, e))?;
+        let mut redirects = Vec::new();
+        let mut retry: Option<RetryInfo> = None;
+
+        // Redirects are followed one hop at a time so each target is checked and reported
+        let resp = loop {
+            // Refuse blocked and restricted destinations before spending a rate-limit token on them
+            self.domains.check_url(&current)?;
+            match self.ssrf.check(&current).await {
+                // Behind a proxy the name may only resolve on the proxy's side
+                Err(SsrfError::Unresolved { .. }) if self.fetch_proxy.is_some() => {}
+                result => result?,
+            }
+            // Rate limiting is per host so fetches never consume the search budget
+            let limiter = self.rate_limits.for_host(current.host_str().unwrap_or_default());
+            if let Some(robots) = &self.robots {
+                robots.check(&current, &limiter).await?;
+            }
+            // Validators belong to the URL the stored copy came from, not to the hops before it
+            let conditional = stored.as_ref().filter(|page| {
+                reqwest::Url::parse(page.final_url().unwrap_or(url)).is_ok_and(|source| source == current)
+            });
+            let (resp, hop_retry) = self.send_fetch(&current, conditional, &limiter).await?;
 
-        // Get main content areas
-        let content_selectors = vec![
-            "article", "main", "[role=\"main\"]", ".content", ".post-content",
-            ".article-content", ".entry-content", "#content", ".main-content"
-        ];
+            let status = resp.status();
+            if was_throttled(status, &hop_retry) {
+                limiter.record_throttled();
+            } else if status.is_success() || status.is_redirection() {
+                limiter.record_success();
+            }
+            match &mut retry {
+                Some(retry) => retry.merge(hop_retry),
+                None => retry = Some(hop_retry),
+            }
 
-        let mut main_content = String::new();
+            let location = resp.headers().get(LOCATION).and_then(|v| v.to_str().ok());
+            let Some(location) = location.filter(|_| is_followed_redirect(status)) else {
+                break resp;
+            };
+            let next = current
+                .join(location)
+                .map_err(|_| RedirectError::InvalidLocation {
+                    from: current.to_string(),
+                    location: location.to_string(),
+                })?;
+            self.redirect_policy.check(&current, &next, redirects.len())?;
+
+            redirects.push(Redirect {
+                status: status.as_u16(),
+                url: next.to_string(),
+            });
+            current = next;
+        };
 
-        // Try to find content in main containers first
-        for selector_str in &content_selectors {
-            if let Ok(selector) = Selector::parse(selector_str) {
-                if let Some(element) = document.select(&selector).next() {
-                    let html = element.html();
-                    let doc = Html::parse_document(&html);
-                    let text = extract_text_content(&doc);
-                    if text.len() > 200 {
-                        main_content = text;
-                        break;
+        let status = resp.status();
+        let mut page = match stored {
+            Some(mut page) if status == StatusCode::NOT_MODIFIED => {
+                page.stored_at = unix_now();
+                Some(page)
+            }
+            _ if status.is_success() => Some(read_page(resp, self.max_fetch_body).await?),
+            _ => None,
+        };
+        if let Some(page) = &mut page {
+            page.redirects = redirects.clone();
+        }
+
+        if let Some(page) = &page {
+            if let Some(disk) = &self.disk_cache {
+                disk.store_page(cache_key, page);
+            }
+            self.fetch_cache.insert(cache_key.to_string(), page.clone());
+        }
+
+        Ok(FetchedPage {
+            status,
+            page,
+            retry: retry.unwrap_or_default(),
+            redirects,
+        })
+    }
+
+    /// One hop of a page fetch, conditional when a stored copy exists, with
+    /// retries that each wait for the host's rate limiter
+    async fn send_fetch(
+        &self,
+        url: &reqwest::Url,
+        stored: Option<&CachedPage>,
+        limiter: &RateLimiter,
+    ) -> Result<(reqwest::Response, RetryInfo)> {
+        self.fetch_retry
+            .send_paced(|| limiter.acquire(), || {
+                let mut request = self
+                    .fetch_client
+                    .get(url.clone())
+                    .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8")
+                    .header("Accept-Language", "en-US,en;q=0.9")
+                    .header("DNT", "1");
+                if let Some(page) = stored {
+                    if let Some(etag) = &page.etag {
+                        request = request.header(IF_NONE_MATCH, etag);
                     }
+                    if let Some(last_modified) = &page.last_modified {
+                        request = request.header(IF_MODIFIED_SINCE, last_modified);
+                    }
+                }
+                request
+            })
+            .await
+            .map_err(|e| {
+                // A DNS answer was refused; say why rather than "error sending request"
+                match e.chain().find_map(|cause| cause.downcast_ref::<SsrfError>()) {
+                    Some(blocked) => anyhow::anyhow!(blocked.clone()),
+                    None => e,
                 }
+            })
+    }
+
+    /// Render a fetched page, cut out the requested window and add its metadata
+    fn render_response(&self, page: &CachedPage, url: &str, options: &FetchOptions) -> Result<FetchResponse> {
+        // Relative links resolve against where the content actually came from
+        let base = page.final_url().unwrap_or(url);
+        let kind = document::classify(page.content_type.as_deref(), page.body.as_bytes());
+        let metadata = match &page.metadata {
+            Some(metadata) => metadata.clone(),
+            None if kind == DocumentKind::Html => metadata::extract(&page.body, base),
+            None => PageMetadata::default(),
+        };
+        let content = match kind {
+            DocumentKind::Html => Self::render_page(&page.body, base, options.format),
+            DocumentKind::Pdf => pdf::render(&page.body, options.pages.as_ref())?,
+            DocumentKind::Json => {
+                document::render_json(&page.body, options.json_path.as_deref(), options.format)?
+            }
+            DocumentKind::Xml if options.format != ContentFormat::Html => {
+                feed::render(&page.body, options.format)
+            }
+            // Plain text and Markdown are already readable; raw XML was asked for
+            _ => page.body.trim().to_string(),
+        };
+        let max_chars = options.max_chars_or(self.default_max_length);
+        let (text, chunk) = content_window(&content, options.start_index, max_chars)?;
+
+        let mut output = String::new();
+        if let Some(header) = metadata.header() {
+            output.push_str(&header);
+            output.push_str("\n\n");
+        }
+        if let Some(note) = redirect_note(url, &page.redirects) {
+            output.push_str(&note);
+            output.push_str("\n\n");
+        }
+
+        if chunk.is_complete() {
+            output.push_str(&format!("Webpage Content:\n\n{}", text));
+        } else {
+            output.push_str(&format!(
+                "Webpage Content (characters {}-{} of {}):\n\n{}",
+                chunk.start_index, chunk.end_index, chunk.total_length, text
+            ));
+            match chunk.next_start_index {
+                Some(next) => output.push_str(&format!(
+                    "\n\n[Content continues: call again with start_index={} to read more]",
+                    next
+                )),
+                None => output.push_str("\n\n[End of content]"),
             }
         }
 
-        // If no main content found, fall back to body
-        if main_content.is_empty() {
-            main_content = extract_text_content(&document);
+        Ok(FetchResponse {
+            url: url.to_string(),
+            final_url: page.final_url().map(str::to_string),
+            redirects: page.redirects.clone(),
+            text: output,
+            metadata: Some(metadata),
+            chunk: Some(chunk),
+            retry: None,
+        })
+    }
+
+    /// Render a fetched page in the requested format
+    fn render_page(html: &str, url: &str, format: ContentFormat) -> String {
+        match format {
+            ContentFormat::Markdown => {
+                let base = reqwest::Url::parse(url).ok();
+                let main_content = readability::extract(html);
+                markdown::to_markdown(&main_content, base.as_ref())
+            }
+            ContentFormat::Text => Self::extract_page_content(html),
+            ContentFormat::Html => html.trim().to_string(),
         }
+    }
+
+    /// Extract readable text from an HTML page
+    fn extract_page_content(html: &str) -> String {
+        // Keep only the main content, then flatten it to readable text
+        let main_content = readability::extract(html);
+        let document = Html::parse_document(&main_content);
+        clean_content(&extract_text_content(&document))
+    }
+}
 
-        // Clean up the content
-        let content = clean_content(&main_content);
+/// Where a fetch ended up and how it got there, when it was redirected
+fn redirect_note(url: &str, redirects: &[Redirect]) -> Option<String> {
+    let last = redirects.last()?;
+    let mut chain = url.to_string();
+    for redirect in redirects {
+        chain.push_str(&format!(" ({}) -> {}", redirect.status, redirect.url));
+    }
+    Some(format!("Final URL: {}\nRedirect chain: {}", last.url, chain))
+}
+
+/// How many search results the domain policy hid
+fn filtered_note(filtered: usize) -> String {
+    match filtered {
+        1 => "1 result from a blocked domain was filtered out.".to_string(),
+        n => format!("{} results from blocked domains were filtered out.", n),
+    }
+}
+
+/// Read a successful response into a cacheable page, keeping its validators
+async fn read_page(resp: reqwest::Response, max_body: u64) -> Result<CachedPage> {
+    let header = |name| {
+        resp.headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+    };
+    let mut content_type = header(CONTENT_TYPE);
+    let etag = header(ETAG);
+    let last_modified = header(LAST_MODIFIED);
+
+    let url = resp.url().to_string();
+
+    // Declared binaries are refused before their body is downloaded
+    if let Some(DocumentKind::Binary(mime)) = document::from_content_type(content_type.as_deref()) {
+        anyhow::bail!("{}", document::binary_refusal(&url, &mime, resp.content_length()));
+    }
+
+    let bytes = body::read_limited(resp, max_body).await?;
+
+    let kind = document::classify(content_type.as_deref(), &bytes);
+    let (body, metadata) = match &kind {
+        DocumentKind::Binary(mime) => {
+            anyhow::bail!("{}", document::binary_refusal(&url, mime, Some(bytes.len() as u64)))
+        }
+        DocumentKind::Pdf => {
+            // Extraction is CPU-bound; keep it off the async workers
+            let (text, metadata) =
+                tokio::task::spawn_blocking(move || pdf::extract(&bytes)).await??;
+            (text, Some(metadata))
+        }
+        // Decode by hand: `text()` only honours the header charset
+        _ => (encoding::decode(&bytes, content_type.as_deref()), None),
+    };
+
+    // Sniffed documents are recorded with their real type so cached copies render the same way
+    if kind == DocumentKind::Pdf || document::from_content_type(content_type.as_deref()).is_none() {
+        content_type = Some(kind.mime_type().to_string());
+    }
+
+    Ok(CachedPage {
+        body,
+        content_type,
+        etag,
+        last_modified,
+        stored_at: unix_now(),
+        metadata,
+        redirects: Vec::new(),
+    })
+}
 
-        Ok(content)
+/// Parse every selector, naming the first invalid one in the error
+fn parse_selectors(selectors: &[String]) -> Result<Vec<(String, Selector)>> {
+    if selectors.is_empty() {
+        anyhow::bail!("At least one CSS selector is required");
     }
+    selectors
+        .iter()
+        .map(|source| {
+            let selector = Selector::parse(source)
+                .map_err(|e| anyhow::anyhow!("Invalid CSS selector '{}': {}", source, e))?;
+            Ok((source.clone(), selector))
+        })
+        .collect()
+}
+
+/// Render the matches of each selector, stopping once `max_length` characters are used
+fn select_elements(
+    html: &str,
+    url: &str,
+    selectors: &[(String, Selector)],
+    options: &SelectorOptions,
+) -> (Vec<SelectorMatches>, bool) {
+    let document = Html::parse_document(html);
+    let base = reqwest::Url::parse(url).ok();
+    let mut budget = options.max_length.max(1);
+    let mut truncated = false;
+
+    let results = selectors
+        .iter()
+        .map(|(source, selector)| {
+            let elements: Vec<_> = document.select(selector).collect();
+            let mut matches = Vec::new();
+
+            for element in elements.iter().take(options.limit.max(1)) {
+                if budget == 0 {
+                    truncated = true;
+                    break;
+                }
+                let rendered = match options.format {
+                    ContentFormat::Markdown => markdown::to_markdown(&element.html(), base.as_ref()),
+                    ContentFormat::Text => {
+                        clean_content(&extract_text_content(&Html::parse_document(&element.html())))
+                    }
+                    ContentFormat::Html => element.html(),
+                };
+
+                let length = rendered.chars().count();
+                if length > budget {
+                    truncated = true;
+                    matches.push(rendered.chars().take(budget).collect());
+                    budget = 0;
+                } else {
+                    budget -= length;
+                    matches.push(rendered);
+                }
+            }
+
+            SelectorMatches {
+                selector: source.clone(),
+                total: elements.len(),
+                matches,
+            }
+        })
+        .collect();
+
+    (results, truncated)
+}
+
+fn http_error(status: StatusCode, url: &str) -> String {
+    format!(
+        "HTTP Error: {} - Failed to fetch content from: {}",
+        status.as_u16(),
+        url
+    )
+}
+
+/// Redirect statuses whose Location is followed; 300 and 304 are answers in their own right
+fn is_followed_redirect(status: StatusCode) -> bool {
+    matches!(status.as_u16(), 301 | 302 | 303 | 307 | 308)
+}
+
+/// Whether the upstream answered 429, either finally or on a retried attempt
+fn was_throttled(status: StatusCode, retry: &RetryInfo) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || retry.throttled
 }
 
 /// Extract text content from HTML document
 fn extract_text_content(document: &Html) -> String {
-    let script_selector = Selector::parse("script, style, nav, footer, header, aside, svg, noscript").unwrap();
-    let mut html = document.html().to_string();
-
     // Simple approach: remove unwanted elements and extract text
     let mut text_parts = Vec::new();
 
@@ -359,6 +1211,51 @@ fn extract_text_content(document: &Html) -> String {
     text_parts.join(" ")
 }
 
+/// Cut a window of at most `max_chars` characters out of `content`.
+///
+/// Windows that stop short of the end are shortened to the last line break
+/// or space in their final fifth, so words and lines are not split between
+/// two reads. Indices count characters, not bytes.
+fn content_window(content: &str, start: usize, max_chars: usize) -> Result<(&str, ContentChunk)> {
+    let total = content.chars().count();
+    if start > total || (start == total && total > 0) {
+        anyhow::bail!(
+            "start_index {} is past the end of the content ({} characters)",
+            start,
+            total
+        );
+    }
+
+    let byte_at = |index: usize| {
+        content
+            .char_indices()
+            .nth(index)
+            .map_or(content.len(), |(i, _)| i)
+    };
+
+    let mut end = (start + max_chars).min(total);
+    if end < total {
+        let window = &content[byte_at(start)..byte_at(end)];
+        let min_end = max_chars - max_chars / 5;
+        let break_at = window
+            .rfind('\n')
+            .or_else(|| window.rfind(char::is_whitespace))
+            .map(|i| window[..i].chars().count() + 1)
+            .filter(|&len| len >= min_end);
+        if let Some(len) = break_at {
+            end = start + len;
+        }
+    }
+
+    let chunk = ContentChunk {
+        start_index: start,
+        end_index: end,
+        total_length: total,
+        next_start_index: (end < total).then_some(end),
+    };
+    Ok((&content[byte_at(start)..byte_at(end)], chunk))
+}
+
 /// Clean up content by removing excessive whitespace
 fn clean_content(content: &str) -> String {
     let mut result = String::new();
@@ -380,28 +1277,14 @@ fn clean_content(content: &str) -> String {
             continue;
         }
 
-        if prev_space {
-            result.push_str(line);
-            result.push(' ');
-        } else {
-            result.push_str(line);
-            result.push(' ');
-        }
+        result.push_str(line);
+        result.push(' ');
         prev_space = false;
         line_count += 1;
-
-        // Limit content size
-        if result.len() > 10000 {
-            result.push_str("\n\n[Content truncated due to size...]");
-            break;
-        }
     }
 
     // Clean up multiple spaces
-    let cleaned = result.split_whitespace().collect::<Vec<_>>().join(" ");
-
-    // Format as lines
-    format!("Webpage Content:\n\n{}", cleaned)
+    result.split_whitespace().collect::<Vec<_>>().join(" ")
 }
 
 impl Default for DuckDuckGoScraper {
@@ -0,0 +1,159 @@
+//! PDF text extraction
+//!
+//! PDFs are converted to text once, when downloaded, and cached as text with
+//! a form feed between pages (as `pdftotext` does), so page ranges can be
+//! selected on every read without parsing the document again.
+
+use crate::models::{PageMetadata, PageRanges};
+use anyhow::Result;
+use pdf_extract::{Document, Object, OutputError, PlainTextOutput, output_doc_page};
+
+/// Separates pages in the cached text
+pub const PAGE_BREAK: char = '\u{c}';
+
+pub const MIME_TYPE: &str = "application/pdf";
+
+/// Per-page text joined by `PAGE_BREAK`, plus the document's metadata
+pub fn extract(bytes: &[u8]) -> Result<(String, PageMetadata)> {
+    // The extractor panics on some malformed files; treat that as a failed extraction
+    let (pages, mut metadata) = std::panic::catch_unwind(|| load(bytes))
+        .map_err(|_| anyhow::anyhow!("Failed to extract text from PDF: malformed document"))?
+        .map_err(|e| anyhow::anyhow!("Failed to extract text from PDF: {}", e))?;
+    metadata.page_count = Some(pages.len());
+
+    // A form feed inside a page would otherwise split it in two when rendered
+    let text = pages
+        .iter()
+        .map(|page| page.replace(PAGE_BREAK, "\n").trim().to_string())
+        .collect::<Vec<_>>()
+        .join(&PAGE_BREAK.to_string());
+    Ok((text, metadata))
+}
+
+/// Parse the document once and read each page's text and the metadata from it
+fn load(bytes: &[u8]) -> std::result::Result<(Vec<String>, PageMetadata), OutputError> {
+    let mut doc = Document::load_mem(bytes)?;
+    if doc.is_encrypted() {
+        // Only documents with an empty user password can be read
+        doc.decrypt("")?;
+    }
+
+    let mut pages = Vec::new();
+    for number in 1.. {
+        let mut page = String::new();
+        if output_doc_page(&doc, &mut PlainTextOutput::new(&mut page), number).is_err() {
+            break;
+        }
+        pages.push(page);
+    }
+    Ok((pages, document_metadata(&doc)))
+}
+
+/// Cached PDF text with page markers, limited to the selected pages
+pub fn render(text: &str, pages: Option<&PageRanges>) -> Result<String> {
+    let all: Vec<&str> = text.split(PAGE_BREAK).collect();
+    if let Some(ranges) = pages
+        && ranges.first() > all.len()
+    {
+        anyhow::bail!(
+            "Page range '{}' is outside the document ({} pages)",
+            ranges,
+            all.len()
+        );
+    }
+
+    let rendered: Vec<String> = all
+        .iter()
+        .enumerate()
+        .map(|(i, page)| (i + 1, page))
+        .filter(|(number, _)| pages.is_none_or(|ranges| ranges.contains(*number)))
+        .map(|(number, page)| format!("--- Page {} ---\n{}", number, page))
+        .collect();
+    Ok(rendered.join("\n\n"))
+}
+
+/// Title, author, subject, dates and language from the Info dictionary and catalog
+fn document_metadata(doc: &Document) -> PageMetadata {
+    let info = doc
+        .trailer
+        .get(b"Info")
+        .ok()
+        .and_then(|info| doc.dereference(info).ok())
+        .and_then(|(_, info)| info.as_dict().ok());
+    let field = |key: &[u8]| {
+        let value = info?.get(key).ok()?;
+        let (_, value) = doc.dereference(value).ok()?;
+        text_string(value)
+    };
+    let language = doc
+        .catalog()
+        .ok()
+        .and_then(|catalog| catalog.get(b"Lang").ok())
+        .and_then(text_string);
+
+    PageMetadata {
+        title: field(b"Title"),
+        author: field(b"Author"),
+        description: field(b"Subject"),
+        published: field(b"CreationDate").map(|date| pdf_date(&date)),
+        modified: field(b"ModDate").map(|date| pdf_date(&date)),
+        language,
+        ..Default::default()
+    }
+}
+
+/// Decode a PDF text string: UTF-16BE with a BOM, otherwise PDFDocEncoding
+/// (treated as Latin-1, which it matches for printable characters)
+fn text_string(value: &Object) -> Option<String> {
+    let Object::String(bytes, _) = value else {
+        return None;
+    };
+    let text = match bytes.strip_prefix(&[0xFE, 0xFF]) {
+        Some(utf16) => {
+            let units: Vec<u16> = utf16
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                .collect();
+            String::from_utf16_lossy(&units)
+        }
+        None => bytes.iter().map(|&b| b as char).collect(),
+    };
+    let text = text.trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+/// Convert "D:YYYYMMDDHHmmSS+HH'mm'" to ISO 8601, keeping unparsable dates as-is
+fn pdf_date(date: &str) -> String {
+    let raw = date.trim().trim_start_matches("D:");
+    let digits: String = raw.chars().take_while(char::is_ascii_digit).collect();
+    if digits.len() < 4 {
+        return date.to_string();
+    }
+
+    let part =
+        |start: usize, default: &'static str| digits.get(start..start + 2).unwrap_or(default);
+    let mut iso = digits[..4].to_string();
+    if digits.len() >= 8 {
+        iso.push_str(&format!("-{}-{}", part(4, "01"), part(6, "01")));
+    }
+    if digits.len() >= 12 {
+        iso.push_str(&format!(
+            "T{}:{}:{}",
+            part(8, "00"),
+            part(10, "00"),
+            part(12, "00")
+        ));
+        let zone = &raw[digits.len()..];
+        match zone.chars().next() {
+            Some('Z') => iso.push('Z'),
+            Some(sign @ ('+' | '-')) => {
+                let offset: String = zone[1..].chars().filter(char::is_ascii_digit).collect();
+                if offset.len() >= 4 {
+                    iso.push_str(&format!("{}{}:{}", sign, &offset[..2], &offset[2..4]));
+                }
+            }
+            _ => {}
+        }
+    }
+    iso
+}
@@ -0,0 +1,478 @@
+//! Readability-style main-content extraction
+//!
+//! Scores paragraph-like elements by text length and comma count, propagates
+//! the scores to their ancestors, weights candidates by tag, class/id hints
+//! and link density, and keeps the best candidate plus related siblings.
+//! Boilerplate (navigation, cookie banners, share bars, comment sections,
+//! link lists) is removed before scoring and again inside the chosen content.
+
+use ego_tree::NodeId;
+use scraper::{ElementRef, Html, Node};
+use std::collections::HashMap;
+
+/// Elements never part of readable content
+const REMOVED_TAGS: &[&str] = &[
+    "script", "style", "noscript", "svg", "iframe", "nav", "aside", "footer", "button", "select",
+    "input", "textarea", "object", "embed", "link", "meta", "template", "dialog", "canvas",
+];
+
+/// Landmark roles that mark page chrome rather than content
+const REMOVED_ROLES: &[&str] = &[
+    "navigation",
+    "complementary",
+    "banner",
+    "contentinfo",
+    "dialog",
+    "alertdialog",
+    "menu",
+    "menubar",
+    "search",
+];
+
+/// class/id hints for elements that are almost never content
+const UNLIKELY: &[&str] = &[
+    "ad",
+    "ads",
+    "advert",
+    "agegate",
+    "banner",
+    "breadcrumb",
+    "combx",
+    "comment",
+    "community",
+    "consent",
+    "cookie",
+    "disqus",
+    "footer",
+    "gdpr",
+    "header",
+    "menu",
+    "modal",
+    "newsletter",
+    "pager",
+    "pagination",
+    "popup",
+    "promo",
+    "related",
+    "remark",
+    "replies",
+    "rss",
+    "share",
+    "shoutbox",
+    "sidebar",
+    "skyscraper",
+    "social",
+    "sponsor",
+    "subscribe",
+    "supplemental",
+    "toolbar",
+];
+
+/// class/id hints that override `UNLIKELY` (e.g. "article-header")
+const MAYBE: &[&str] = &[
+    "article", "body", "column", "content", "main", "post", "story",
+];
+
+const POSITIVE: &[&str] = &[
+    "article", "blog", "body", "content", "entry", "hentry", "main", "page", "post", "story",
+    "text",
+];
+
+const NEGATIVE: &[&str] = &[
+    "ad",
+    "banner",
+    "byline",
+    "combx",
+    "comment",
+    "contact",
+    "consent",
+    "cookie",
+    "footer",
+    "footnote",
+    "gdpr",
+    "hidden",
+    "masthead",
+    "meta",
+    "newsletter",
+    "outbrain",
+    "promo",
+    "related",
+    "scroll",
+    "share",
+    "shopping",
+    "shoutbox",
+    "sidebar",
+    "skyscraper",
+    "social",
+    "sponsor",
+    "subscribe",
+    "tags",
+    "tool",
+    "widget",
+];
+
+/// Paragraph-like elements whose text seeds candidate scores
+const SCORED_TAGS: &[&str] = &[
+    "p",
+    "pre",
+    "td",
+    "blockquote",
+    "section",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+];
+
+/// Containers removed from the chosen content when they look like boilerplate
+const CLEANED_TAGS: &[&str] = &[
+    "div", "section", "ul", "ol", "table", "form", "header", "h1", "h2", "h3", "h4", "h5", "h6",
+];
+
+const BLOCK_TAGS: &[&str] = &[
+    "address",
+    "article",
+    "blockquote",
+    "div",
+    "dl",
+    "fieldset",
+    "figure",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "main",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "ul",
+];
+
+const MIN_PARAGRAPH_LEN: usize = 25;
+
+/// HTML of the page's main content, or of the cleaned body when no
+/// candidate stands out (short pages, unusual markup)
+pub fn extract(html: &str) -> String {
+    let mut document = Html::parse_document(html);
+    remove_nodes(&mut document, is_unlikely);
+
+    let Some(top) = top_candidate(&document) else {
+        return body_or_root(&document).html();
+    };
+    let selected = with_related_siblings(&document, top);
+
+    // Conditional cleaning inside the chosen content
+    remove_nodes(&mut document, |el| {
+        !selected.contains(&el.id())
+            && el.ancestors().any(|a| selected.contains(&a.id()))
+            && is_boilerplate(el)
+    });
+
+    selected
+        .iter()
+        .filter_map(|id| document.tree.get(*id).and_then(ElementRef::wrap))
+        .map(|el| el.html())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn body_or_root(document: &Html) -> ElementRef<'_> {
+    document
+        .root_element()
+        .children()
+        .filter_map(ElementRef::wrap)
+        .find(|el| el.value().name() == "body")
+        .unwrap_or_else(|| document.root_element())
+}
+
+/// Detach every element matching `predicate`; nested matches are detached too, which is harmless
+fn remove_nodes(document: &mut Html, predicate: impl Fn(ElementRef) -> bool) {
+    let doomed: Vec<NodeId> = document
+        .root_element()
+        .descendants()
+        .filter_map(ElementRef::wrap)
+        .filter(|el| predicate(*el))
+        .map(|el| el.id())
+        .collect();
+
+    for id in doomed {
+        if let Some(mut node) = document.tree.get_mut(id) {
+            node.detach();
+        }
+    }
+}
+
+fn is_unlikely(el: ElementRef) -> bool {
+    let name = el.value().name();
+    if matches!(name, "html" | "body" | "article" | "main") {
+        return false;
+    }
+    if REMOVED_TAGS.contains(&name) {
+        return true;
+    }
+    // Page headers go, but headers inside an article keep the title
+    if name == "header"
+        && !el
+            .ancestors()
+            .filter_map(ElementRef::wrap)
+            .any(|a| matches!(a.value().name(), "article" | "main"))
+    {
+        return true;
+    }
+    if el.value().attr("hidden").is_some()
+        || el.value().attr("aria-hidden") == Some("true")
+        || el
+            .value()
+            .attr("style")
+            .is_some_and(|s| s.replace(' ', "").contains("display:none"))
+    {
+        return true;
+    }
+    if el
+        .value()
+        .attr("role")
+        .is_some_and(|role| REMOVED_ROLES.contains(&role))
+    {
+        return true;
+    }
+
+    let hints = hints(el);
+    matches_any(&hints, UNLIKELY) && !matches_any(&hints, MAYBE)
+}
+
+/// Lowercased class and id tokens
+fn hints(el: ElementRef) -> Vec<String> {
+    let mut tokens = Vec::new();
+    for attr in ["class", "id"] {
+        if let Some(value) = el.value().attr(attr) {
+            tokens.extend(
+                value
+                    .split(|c: char| !c.is_ascii_alphanumeric())
+                    .filter(|t| !t.is_empty())
+                    .map(|t| t.to_ascii_lowercase()),
+            );
+        }
+    }
+    tokens
+}
+
+/// Short keywords must match a whole token; longer ones may prefix it
+/// ("comment" matches "comments", "ad" does not match "header")
+fn matches_any(tokens: &[String], keywords: &[&str]) -> bool {
+    tokens.iter().any(|token| {
+        keywords
+            .iter()
+            .any(|kw| token == kw || (kw.len() >= 4 && token.starts_with(kw)))
+    })
+}
+
+fn class_weight(el: ElementRef) -> f64 {
+    let hints = hints(el);
+    let mut weight = 0.0;
+    if matches_any(&hints, NEGATIVE) {
+        weight -= 25.0;
+    }
+    if matches_any(&hints, POSITIVE) {
+        weight += 25.0;
+    }
+    weight
+}
+
+fn tag_weight(name: &str) -> f64 {
+    match name {
+        "article" => 10.0,
+        "div" | "main" => 5.0,
+        "pre" | "td" | "blockquote" => 3.0,
+        "address" | "ol" | "ul" | "dl" | "dd" | "dt" | "li" | "form" => -3.0,
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th" => -5.0,
+        _ => 0.0,
+    }
+}
+
+/// Whitespace-collapsed text content
+fn inner_text(el: ElementRef) -> String {
+    el.text()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn text_len(el: ElementRef) -> usize {
+    inner_text(el).chars().count()
+}
+
+/// Share of the element's text that sits inside links (fragment links count less)
+fn link_density(el: ElementRef) -> f64 {
+    let total = text_len(el);
+    if total == 0 {
+        return 0.0;
+    }
+    let linked: f64 = el
+        .descendants()
+        .filter_map(ElementRef::wrap)
+        .filter(|a| a.value().name() == "a")
+        .map(|a| {
+            let coefficient = if a.value().attr("href").is_some_and(|h| h.starts_with('#')) {
+                0.3
+            } else {
+                1.0
+            };
+            text_len(a) as f64 * coefficient
+        })
+        .sum();
+    (linked / total as f64).min(1.0)
+}
+
+/// A div only counts as a paragraph when it has no block-level children
+fn is_paragraph_like(el: ElementRef) -> bool {
+    let name = el.value().name();
+    if SCORED_TAGS.contains(&name) {
+        return true;
+    }
+    name == "div"
+        && !el
+            .children()
+            .filter_map(ElementRef::wrap)
+            .any(|child| BLOCK_TAGS.contains(&child.value().name()))
+}
+
+fn top_candidate(document: &Html) -> Option<NodeId> {
+    let mut scores: HashMap<NodeId, f64> = HashMap::new();
+
+    for el in document
+        .root_element()
+        .descendants()
+        .filter_map(ElementRef::wrap)
+    {
+        if !is_paragraph_like(el) {
+            continue;
+        }
+        let text = inner_text(el);
+        let len = text.chars().count();
+        if len < MIN_PARAGRAPH_LEN {
+            continue;
+        }
+
+        let commas = text.matches([',', '，', '、']).count() as f64;
+        let score = 1.0 + commas + (len as f64 / 100.0).min(3.0);
+
+        for (level, ancestor) in el
+            .ancestors()
+            .filter_map(ElementRef::wrap)
+            .take(3)
+            .enumerate()
+        {
+            if matches!(ancestor.value().name(), "html" | "body") {
+                break;
+            }
+            let entry = scores
+                .entry(ancestor.id())
+                .or_insert_with(|| tag_weight(ancestor.value().name()) + class_weight(ancestor));
+            let divider = match level {
+                0 => 1.0,
+                1 => 2.0,
+                n => n as f64 * 3.0,
+            };
+            *entry += score / divider;
+        }
+    }
+
+    scores
+        .into_iter()
+        .filter_map(|(id, score)| {
+            let el = ElementRef::wrap(document.tree.get(id)?)?;
+            Some((id, score * (1.0 - link_density(el))))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .filter(|(_, score)| *score > 0.0)
+        .map(|(id, _)| id)
+}
+
+/// The top candidate plus siblings that look like part of the same content,
+/// in document order
+fn with_related_siblings(document: &Html, top: NodeId) -> Vec<NodeId> {
+    let Some(top_el) = document.tree.get(top).and_then(ElementRef::wrap) else {
+        return vec![top];
+    };
+    let Some(parent) = top_el.parent() else {
+        return vec![top];
+    };
+
+    let top_score = candidate_score(top_el);
+    let threshold = (top_score * 0.2).max(10.0);
+    let top_class = top_el.value().attr("class");
+
+    parent
+        .children()
+        .filter_map(ElementRef::wrap)
+        .filter(|sibling| {
+            if sibling.id() == top {
+                return true;
+            }
+            let mut bonus = 0.0;
+            if top_class.is_some() && sibling.value().attr("class") == top_class {
+                bonus += top_score * 0.2;
+            }
+            if candidate_score(*sibling) + bonus >= threshold {
+                return true;
+            }
+            if sibling.value().name() == "p" {
+                let text = inner_text(*sibling);
+                let len = text.chars().count();
+                let density = link_density(*sibling);
+                return (len > 80 && density < 0.25)
+                    || (len > 0 && density == 0.0 && text.contains(". "));
+            }
+            false
+        })
+        .map(|el| el.id())
+        .collect()
+}
+
+/// Score of one element computed from its own paragraphs (used for siblings)
+fn candidate_score(el: ElementRef) -> f64 {
+    let content: f64 = el
+        .descendants()
+        .filter_map(ElementRef::wrap)
+        .filter(|p| is_paragraph_like(*p))
+        .map(inner_text)
+        .filter(|t| t.chars().count() >= MIN_PARAGRAPH_LEN)
+        .map(|t| 1.0 + t.matches(',').count() as f64 + (t.chars().count() as f64 / 100.0).min(3.0))
+        .sum();
+    (tag_weight(el.value().name()) + class_weight(el) + content) * (1.0 - link_density(el))
+}
+
+fn is_boilerplate(el: ElementRef) -> bool {
+    let name = el.value().name();
+    if !CLEANED_TAGS.contains(&name) {
+        return false;
+    }
+    if class_weight(el) < 0.0 {
+        return true;
+    }
+
+    let text = inner_text(el);
+    if text.matches(',').count() >= 10 {
+        return false;
+    }
+    let has_media = el.descendants().any(|n| match n.value() {
+        Node::Element(e) => matches!(e.name(), "img" | "pre" | "code" | "table" | "video"),
+        _ => false,
+    });
+    let heading = name.starts_with('h') && name.len() == 2;
+
+    if heading {
+        return link_density(el) > 0.33;
+    }
+    link_density(el) > 0.5
+        || (text.chars().count() < MIN_PARAGRAPH_LEN && !has_media && name != "table")
+}
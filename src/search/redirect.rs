@@ -0,0 +1,91 @@
+//! Redirect policy: hop limit, scheme downgrades, and per-hop destination checks
+
+use crate::domains::DomainPolicy;
+use crate::ssrf::SsrfPolicy;
+use reqwest::Url;
+use std::sync::Arc;
+
+/// How redirects are followed
+#[derive(Debug, Clone)]
+pub struct RedirectPolicy {
+    /// Redirects followed before the request fails (0 disables following)
+    pub max_hops: usize,
+    /// Follow redirects from https to http
+    pub allow_downgrade: bool,
+}
+
+impl Default for RedirectPolicy {
+    fn default() -> Self {
+        Self {
+            max_hops: 10,
+            allow_downgrade: false,
+        }
+    }
+}
+
+/// Why a redirect was not followed
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RedirectError {
+    #[error("Too many redirects: {url} redirected more than {max} times")]
+    TooMany { url: String, max: usize },
+    #[error("Refusing to follow a redirect from {from} to {to}: it downgrades https to http")]
+    Downgrade { from: String, to: String },
+    #[error("Invalid redirect from {from}: Location '{location}' is not a valid URL")]
+    InvalidLocation { from: String, location: String },
+}
+
+impl RedirectPolicy {
+    pub fn new(max_hops: usize) -> Self {
+        Self {
+            max_hops,
+            ..Self::default()
+        }
+    }
+
+    /// Policy that returns redirect responses instead of following them
+    pub fn none() -> Self {
+        Self::new(0)
+    }
+
+    /// Whether the hop after `followed` earlier redirects, from `from` to `to`, may be taken
+    pub fn check(&self, from: &Url, to: &Url, followed: usize) -> Result<(), RedirectError> {
+        if followed >= self.max_hops {
+            return Err(RedirectError::TooMany {
+                url: from.to_string(),
+                max: self.max_hops,
+            });
+        }
+        if !self.allow_downgrade && from.scheme() == "https" && to.scheme() == "http" {
+            return Err(RedirectError::Downgrade {
+                from: from.to_string(),
+                to: to.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// The same rules, plus the SSRF and domain policies, for requests reqwest redirects itself
+    pub(crate) fn for_client(
+        &self,
+        ssrf: &Arc<SsrfPolicy>,
+        domains: &Arc<DomainPolicy>,
+    ) -> reqwest::redirect::Policy {
+        let (policy, ssrf, domains) = (self.clone(), ssrf.clone(), domains.clone());
+        reqwest::redirect::Policy::custom(move |attempt| {
+            let from = attempt.previous().last().cloned();
+            let hops = attempt.previous().len().saturating_sub(1);
+            let checked = from
+                .map(|from| policy.check(&from, attempt.url(), hops))
+                .transpose();
+            if let Err(refused) = checked {
+                attempt.error(refused)
+            } else if let Err(blocked) = ssrf.check_url(attempt.url()) {
+                attempt.error(blocked)
+            } else if let Err(blocked) = domains.check_url(attempt.url()) {
+                attempt.error(blocked)
+            } else {
+                attempt.follow()
+            }
+        })
+    }
+}
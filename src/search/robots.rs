@@ -0,0 +1,346 @@
+//! robots.txt compliance for page fetches (RFC 9309)
+
+use super::body;
+use crate::cache::{CacheConfig, SingleFlight, TtlCache};
+use crate::ratelimit::RateLimiter;
+use reqwest::{StatusCode, Url};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Part of a robots.txt that is parsed; RFC 9309 asks crawlers to parse at least 500 KiB
+const MAX_ROBOTS_BODY: u64 = 512 * 1024;
+
+/// Hosts whose robots.txt is kept at once
+const MAX_CACHED_HOSTS: usize = 500;
+
+/// How long an unreadable robots.txt keeps refusing fetches before it is tried again
+const UNREACHABLE_TTL: Duration = Duration::from_secs(60);
+
+/// How robots.txt files are applied
+#[derive(Debug, Clone)]
+pub struct RobotsConfig {
+    /// Product token matched against `User-agent` lines
+    pub user_agent: String,
+    /// How long a host's robots.txt is reused before it is fetched again
+    pub cache_ttl: Duration,
+    /// Longest wait honoured for `Crawl-delay`; a fetch that would wait longer is refused
+    pub max_crawl_wait: Duration,
+}
+
+impl Default for RobotsConfig {
+    fn default() -> Self {
+        Self {
+            user_agent: "mcp-websearch".to_string(),
+            cache_ttl: Duration::from_secs(3600),
+            max_crawl_wait: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RobotsConfig {
+    pub fn new(user_agent: impl Into<String>) -> Self {
+        Self {
+            user_agent: user_agent.into(),
+            ..Self::default()
+        }
+    }
+}
+
+/// Why robots.txt prevented a fetch
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RobotsError {
+    #[error("Fetching {url} is disallowed by {robots_url} (rule '{rule}' for user-agent '{user_agent}')")]
+    Disallowed {
+        url: String,
+        robots_url: String,
+        rule: String,
+        user_agent: String,
+    },
+    #[error("{robots_url} could not be read ({reason}), so the site is treated as disallowed")]
+    Unreachable { robots_url: String, reason: String },
+    #[error(
+        "{robots_url} asks for {} seconds between requests; the next slot is {} seconds away",
+        .delay.as_secs_f64(),
+        .wait.as_secs()
+    )]
+    CrawlDelay {
+        robots_url: String,
+        delay: Duration,
+        wait: Duration,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Rule {
+    allow: bool,
+    pattern: String,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Group {
+    agents: Vec<String>,
+    rules: Vec<Rule>,
+    crawl_delay: Option<Duration>,
+}
+
+/// A parsed robots.txt file
+#[derive(Debug, Clone, Default)]
+pub struct RobotsTxt {
+    groups: Vec<Group>,
+}
+
+impl RobotsTxt {
+    /// Parse a robots.txt body; unknown lines and rules outside a group are ignored
+    pub fn parse(text: &str) -> Self {
+        let mut groups: Vec<Group> = Vec::new();
+        let mut in_agents = false;
+
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+
+            match key.trim().to_ascii_lowercase().as_str() {
+                "user-agent" => {
+                    // Consecutive User-agent lines share one group
+                    if !in_agents {
+                        groups.push(Group::default());
+                    }
+                    in_agents = true;
+                    let agent = value.split('/').next().unwrap_or_default().trim();
+                    groups.last_mut().unwrap().agents.push(agent.to_ascii_lowercase());
+                }
+                key @ ("allow" | "disallow") => {
+                    in_agents = false;
+                    // An empty Disallow allows everything, so it adds no rule
+                    if let Some(group) = groups.last_mut()
+                        && !value.is_empty()
+                    {
+                        group.rules.push(Rule {
+                            allow: key == "allow",
+                            pattern: value.to_string(),
+                        });
+                    }
+                }
+                "crawl-delay" => {
+                    in_agents = false;
+                    if let Some(group) = groups.last_mut()
+                        && let Ok(secs) = value.parse::<f64>()
+                        && secs.is_finite()
+                        && secs >= 0.0
+                    {
+                        group.crawl_delay = Some(Duration::from_secs_f64(secs));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Self { groups }
+    }
+
+    /// Groups for this user agent: those naming it, or else the `*` groups
+    fn groups_for(&self, user_agent: &str) -> Vec<&Group> {
+        let token = user_agent.to_ascii_lowercase();
+        let named: Vec<&Group> = self
+            .groups
+            .iter()
+            .filter(|g| g.agents.contains(&token))
+            .collect();
+        if !named.is_empty() {
+            return named;
+        }
+        self.groups
+            .iter()
+            .filter(|g| g.agents.iter().any(|a| a == "*"))
+            .collect()
+    }
+
+    /// The rule deciding a path, as written in the file, if it is disallowed.
+    ///
+    /// The longest matching pattern wins, and Allow wins a tie.
+    pub fn disallowed_by(&self, user_agent: &str, path: &str) -> Option<String> {
+        if path == "/robots.txt" {
+            return None;
+        }
+
+        let groups = self.groups_for(user_agent);
+        let best = groups
+            .iter()
+            .flat_map(|g| &g.rules)
+            .filter(|rule| pattern_matches(&rule.pattern, path))
+            .max_by_key(|rule| (rule.pattern.len(), rule.allow))?;
+
+        (!best.allow).then(|| format!("Disallow: {}", best.pattern))
+    }
+
+    /// Crawl-delay for this user agent, if its group sets one
+    pub fn crawl_delay(&self, user_agent: &str) -> Option<Duration> {
+        self.groups_for(user_agent)
+            .iter()
+            .filter_map(|g| g.crawl_delay)
+            .max()
+    }
+}
+
+/// Match a path against a pattern where `*` is any run of characters and a trailing `$` anchors the end
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let parts: Vec<&str> = pattern.split('*').collect();
+
+    let Some(mut rest) = path.strip_prefix(parts[0]) else {
+        return false;
+    };
+    for (i, part) in parts.iter().enumerate().skip(1) {
+        let last = i == parts.len() - 1;
+        if last && anchored {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    !anchored || rest.is_empty()
+}
+
+/// Fetches, caches and applies each host's robots.txt
+pub(crate) struct RobotsChecker {
+    config: RobotsConfig,
+    /// Client that follows redirects, set by the scraper
+    client: reqwest::Client,
+    cache: TtlCache<String, Arc<RobotsTxt>>,
+    /// Recent failures, so an unreachable robots.txt is not requested before every fetch
+    failures: TtlCache<String, RobotsError>,
+    /// Concurrent first requests to an origin share one robots.txt download
+    inflight: SingleFlight<String, Arc<RobotsTxt>, RobotsError>,
+    /// Earliest time the next request to each origin may start under its Crawl-delay
+    next_slot: Mutex<HashMap<String, Instant>>,
+}
+
+impl RobotsChecker {
+    pub fn new(config: RobotsConfig) -> Self {
+        let cache = TtlCache::new(&CacheConfig::new(MAX_CACHED_HOSTS, config.cache_ttl));
+        let failures = TtlCache::new(&CacheConfig::new(MAX_CACHED_HOSTS, UNREACHABLE_TTL));
+        Self {
+            config,
+            client: reqwest::Client::new(),
+            cache,
+            failures,
+            inflight: SingleFlight::new(),
+            next_slot: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn set_client(&mut self, client: reqwest::Client) {
+        self.client = client;
+    }
+
+    /// Refuse a disallowed URL, and wait out the host's Crawl-delay otherwise.
+    ///
+    /// A robots.txt download counts against `limiter`, the host's fetch budget.
+    pub async fn check(&self, url: &Url, limiter: &RateLimiter) -> Result<(), RobotsError> {
+        let origin = url.origin().ascii_serialization();
+        let robots_url = format!("{}/robots.txt", origin);
+        let robots = match self.cache.get(&origin) {
+            Some(robots) => robots,
+            None => {
+                if let Some(err) = self.failures.get(&origin) {
+                    return Err(err);
+                }
+                self.inflight
+                    .run(origin.clone(), || async {
+                        limiter.acquire().await;
+                        match fetch(&self.client, &robots_url).await {
+                            Ok(robots) => {
+                                let robots = Arc::new(robots);
+                                self.cache.insert(origin.clone(), robots.clone());
+                                Ok(robots)
+                            }
+                            Err(err) => {
+                                self.failures.insert(origin.clone(), err.clone());
+                                Err(err)
+                            }
+                        }
+                    })
+                    .await?
+            }
+        };
+
+        let mut path = url.path().to_string();
+        if let Some(query) = url.query() {
+            path.push('?');
+            path.push_str(query);
+        }
+        if let Some(rule) = robots.disallowed_by(&self.config.user_agent, &path) {
+            return Err(RobotsError::Disallowed {
+                url: url.to_string(),
+                robots_url,
+                rule,
+                user_agent: self.config.user_agent.clone(),
+            });
+        }
+
+        if let Some(delay) = robots.crawl_delay(&self.config.user_agent) {
+            let wait = self.reserve(&origin, delay).map_err(|wait| RobotsError::CrawlDelay {
+                robots_url,
+                delay,
+                wait,
+            })?;
+            tokio::time::sleep(wait).await;
+        }
+        Ok(())
+    }
+
+    /// Claim the next request slot for an origin, returning how long to wait for it
+    fn reserve(&self, origin: &str, delay: Duration) -> Result<Duration, Duration> {
+        let mut slots = self.next_slot.lock().unwrap();
+        let now = Instant::now();
+        let start = slots.get(origin).copied().filter(|t| *t > now).unwrap_or(now);
+        let wait = start - now;
+        if wait > self.config.max_crawl_wait {
+            return Err(wait);
+        }
+        slots.insert(origin.to_string(), start + delay);
+        Ok(wait)
+    }
+}
+
+/// Download robots.txt: 4xx means no restrictions; 429, 5xx or no answer means full restriction
+async fn fetch(client: &reqwest::Client, robots_url: &str) -> Result<RobotsTxt, RobotsError> {
+    let unreachable = |reason: String| RobotsError::Unreachable {
+        robots_url: robots_url.to_string(),
+        reason,
+    };
+
+    let resp = client
+        .get(robots_url)
+        .send()
+        .await
+        .map_err(|e| unreachable(e.to_string()))?;
+    let status = resp.status();
+    // Being throttled says nothing about the rules; back off as for a server error
+    if status.is_client_error() && status != StatusCode::TOO_MANY_REQUESTS {
+        return Ok(RobotsTxt::default());
+    }
+    if !status.is_success() {
+        return Err(unreachable(format!("HTTP {}", status.as_u16())));
+    }
+
+    // Oversized files are parsed up to the limit (RFC 9309 section 2.5), minus any cut-off line
+    let (mut bytes, truncated) = body::read_prefix(resp, MAX_ROBOTS_BODY)
+        .await
+        .map_err(|e| unreachable(e.to_string()))?;
+    if truncated {
+        let complete = bytes.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+        bytes.truncate(complete);
+    }
+    Ok(RobotsTxt::parse(&String::from_utf8_lossy(&bytes)))
+}
@@ -0,0 +1,264 @@
+//! Server-side request forgery protection for page fetches
+//!
+//! Model-supplied URLs must not reach loopback, private, link-local or cloud
+//! metadata addresses. Destinations are checked when the URL is validated,
+//! again when its hostname is resolved (so a DNS answer cannot swap in a
+//! private address after validation), and on every redirect hop.
+
+use anyhow::Result;
+use ipnet::IpNet;
+use reqwest::Url;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+
+/// Why a destination was refused
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SsrfError {
+    #[error("Only http and https URLs can be fetched, not '{0}:' URLs")]
+    Scheme(String),
+    #[error("URL '{0}' has no host")]
+    MissingHost(String),
+    #[error(
+        "Blocked request to {host}: {ip} is a {kind} address. Add the host to the fetch allowlist to reach intranet services"
+    )]
+    Restricted {
+        host: String,
+        ip: IpAddr,
+        kind: &'static str,
+    },
+    #[error("Could not resolve host {host}: {reason}")]
+    Unresolved { host: String, reason: String },
+}
+
+/// Which destinations page fetches may reach.
+///
+/// Public addresses are always allowed. Restricted addresses are reachable
+/// only through an allowlist entry: an exact host (`wiki.corp.example`), a
+/// subdomain wildcard (`*.corp.example`), an IP address or a CIDR range
+/// (`10.20.0.0/16`).
+#[derive(Debug, Clone, Default)]
+pub struct SsrfPolicy {
+    hosts: Vec<String>,
+    suffixes: Vec<String>,
+    networks: Vec<IpNet>,
+}
+
+impl SsrfPolicy {
+    /// Policy that blocks every restricted address
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Policy with allowlist entries, rejecting any it cannot parse
+    pub fn with_allowlist<S: AsRef<str>>(entries: &[S]) -> Result<Self> {
+        let mut policy = Self::new();
+        for entry in entries {
+            let entry = entry.as_ref().trim();
+            if entry.is_empty() {
+                continue;
+            }
+            if let Ok(network) = entry.parse::<IpNet>() {
+                policy.networks.push(network);
+            } else if let Ok(ip) = entry.parse::<IpAddr>() {
+                policy.networks.push(IpNet::from(ip));
+            } else if let Some(suffix) = entry.strip_prefix("*.") {
+                policy.suffixes.push(normalize_host(suffix));
+            } else if entry.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_')) {
+                policy.hosts.push(normalize_host(entry));
+            } else {
+                anyhow::bail!(
+                    "Invalid fetch allowlist entry '{}': expected a host, *.domain, IP address or CIDR range",
+                    entry
+                );
+            }
+        }
+        Ok(policy)
+    }
+
+    /// Copy of this policy that also trusts `host`, such as a configured proxy on a private address
+    pub(crate) fn trusting_host(&self, host: &str) -> Self {
+        let mut policy = self.clone();
+        match host.trim_matches(|c| c == '[' || c == ']').parse::<IpAddr>() {
+            Ok(ip) => policy.networks.push(IpNet::from(ip)),
+            Err(_) => policy.hosts.push(normalize_host(host)),
+        }
+        policy
+    }
+
+    /// Whether a hostname is allowlisted, skipping address checks entirely
+    pub fn allows_host(&self, host: &str) -> bool {
+        let host = normalize_host(host);
+        self.hosts.contains(&host)
+            || self
+                .suffixes
+                .iter()
+                .any(|suffix| host.ends_with(suffix.as_str()) && host[..host.len() - suffix.len()].ends_with('.'))
+    }
+
+    /// Check an address the host resolved to (or an IP literal)
+    pub fn check_ip(&self, host: &str, ip: IpAddr) -> Result<(), SsrfError> {
+        let Some(kind) = restriction(ip) else {
+            return Ok(());
+        };
+        if self.networks.iter().any(|net| net.contains(&ip)) || self.allows_host(host) {
+            return Ok(());
+        }
+        Err(SsrfError::Restricted {
+            host: host.to_string(),
+            ip,
+            kind,
+        })
+    }
+
+    /// Checks that need no DNS lookup: the scheme, IP literals and `localhost`
+    pub fn check_url(&self, url: &Url) -> Result<(), SsrfError> {
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(SsrfError::Scheme(url.scheme().to_string()));
+        }
+        let host = url
+            .host_str()
+            .ok_or_else(|| SsrfError::MissingHost(url.to_string()))?;
+
+        match ip_literal(host) {
+            Some(ip) => self.check_ip(host, ip),
+            // `localhost` never reaches DNS on most systems, so refuse it by name
+            None if is_local_name(host) => self.check_ip(host, IpAddr::V4(Ipv4Addr::LOCALHOST)),
+            None => Ok(()),
+        }
+    }
+
+    /// Full check of a URL, resolving its hostname
+    pub async fn check(&self, url: &Url) -> Result<(), SsrfError> {
+        self.check_url(url)?;
+        if let Some(host) = url.host_str()
+            && ip_literal(host).is_none()
+            && !is_local_name(host)
+        {
+            self.resolve(host).await?;
+        }
+        Ok(())
+    }
+
+    /// Resolve a hostname, failing if any address it maps to is restricted
+    pub async fn resolve(&self, host: &str) -> Result<Vec<SocketAddr>, SsrfError> {
+        let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, 0))
+            .await
+            .map_err(|e| SsrfError::Unresolved {
+                host: host.to_string(),
+                reason: e.to_string(),
+            })?
+            .collect();
+        for addr in &addrs {
+            self.check_ip(host, addr.ip())?;
+        }
+        Ok(addrs)
+    }
+}
+
+/// DNS resolver for the HTTP client that applies an `SsrfPolicy` to every answer
+pub(crate) struct GuardedResolver {
+    policy: Arc<SsrfPolicy>,
+}
+
+impl GuardedResolver {
+    pub fn new(policy: Arc<SsrfPolicy>) -> Self {
+        Self { policy }
+    }
+}
+
+impl Resolve for GuardedResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let policy = self.policy.clone();
+        Box::pin(async move {
+            let addrs = policy.resolve(name.as_str()).await?;
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// The policy violation behind an error, if any, looking through wrapped sources
+pub fn blocked_reason<'a>(err: &'a (dyn std::error::Error + 'static)) -> Option<&'a SsrfError> {
+    let mut current = Some(err);
+    while let Some(err) = current {
+        if let Some(blocked) = err.downcast_ref::<SsrfError>() {
+            return Some(blocked);
+        }
+        current = err.source();
+    }
+    None
+}
+
+/// Why an address must not be fetched, or None for public addresses
+pub fn restriction(ip: IpAddr) -> Option<&'static str> {
+    match ip {
+        IpAddr::V4(ip) => restriction_v4(ip),
+        IpAddr::V6(ip) => restriction_v6(ip),
+    }
+}
+
+fn restriction_v4(ip: Ipv4Addr) -> Option<&'static str> {
+    let [a, b, c, _] = ip.octets();
+    Some(match (a, b, c) {
+        (169, 254, 169) if ip.octets()[3] == 254 => "cloud metadata",
+        (100, 100, 100) if ip.octets()[3] == 200 => "cloud metadata",
+        (0, _, _) => "unspecified",
+        (127, _, _) => "loopback",
+        (10, _, _) | (192, 168, _) => "private",
+        (172, 16..=31, _) => "private",
+        (169, 254, _) => "link-local",
+        (100, 64..=127, _) => "shared (carrier-grade NAT)",
+        (192, 0, 0) | (198, 18..=19, _) => "reserved",
+        (224..=239, _, _) => "multicast",
+        (240..=255, _, _) => "reserved",
+        _ => return None,
+    })
+}
+
+fn restriction_v6(ip: Ipv6Addr) -> Option<&'static str> {
+    // IPv4-mapped, IPv4-compatible, NAT64 and 6to4 addresses reach the embedded IPv4 address
+    if let Some(v4) = ip.to_ipv4_mapped() {
+        return restriction_v4(v4);
+    }
+    let segments = ip.segments();
+    let octets = ip.octets();
+    if segments[..6] == [0; 6] && !ip.is_unspecified() && !ip.is_loopback() {
+        return restriction_v4(Ipv4Addr::new(octets[12], octets[13], octets[14], octets[15]));
+    }
+    if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+        return restriction_v4(Ipv4Addr::new(octets[12], octets[13], octets[14], octets[15]));
+    }
+    if segments[0] == 0x2002 {
+        return restriction_v4(Ipv4Addr::new(octets[2], octets[3], octets[4], octets[5]));
+    }
+
+    Some(match segments[0] {
+        _ if ip.is_unspecified() => "unspecified",
+        _ if ip.is_loopback() => "loopback",
+        _ if segments == [0xfd00, 0xec2, 0, 0, 0, 0, 0, 0x254] => "cloud metadata",
+        0xfc00..=0xfdff => "private",
+        0xfe80..=0xfebf => "link-local",
+        0xfec0..=0xfeff => "site-local",
+        0xff00..=0xffff => "multicast",
+        0x0100 if segments[1..4] == [0, 0, 0] => "reserved",
+        _ => return None,
+    })
+}
+
+/// The address of an IP-literal host (IPv6 hosts keep their brackets in URLs)
+fn ip_literal(host: &str) -> Option<IpAddr> {
+    host.trim_start_matches('[').trim_end_matches(']').parse().ok()
+}
+
+/// `localhost` and names under it, which resolve to loopback by convention
+fn is_local_name(host: &str) -> bool {
+    let host = normalize_host(host);
+    host == "localhost" || host.ends_with(".localhost")
+}
+
+fn normalize_host(host: &str) -> String {
+    host.trim().trim_end_matches('.').to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,158 @@
+//! Unit tests for SSRF protection
+
+use super::*;
+
+fn ip(s: &str) -> IpAddr {
+    s.parse().unwrap()
+}
+
+fn url(s: &str) -> Url {
+    Url::parse(s).unwrap()
+}
+
+#[test]
+fn test_restricted_ipv4_ranges() {
+    for (addr, kind) in [
+        ("127.0.0.1", "loopback"),
+        ("127.255.0.9", "loopback"),
+        ("10.1.2.3", "private"),
+        ("172.16.0.1", "private"),
+        ("172.31.255.255", "private"),
+        ("192.168.1.1", "private"),
+        ("169.254.169.254", "cloud metadata"),
+        ("100.100.100.200", "cloud metadata"),
+        ("169.254.10.1", "link-local"),
+        ("100.64.0.1", "shared (carrier-grade NAT)"),
+        ("0.0.0.0", "unspecified"),
+        ("224.0.0.1", "multicast"),
+        ("255.255.255.255", "reserved"),
+        ("198.18.0.1", "reserved"),
+    ] {
+        assert_eq!(restriction(ip(addr)), Some(kind), "{}", addr);
+    }
+}
+
+#[test]
+fn test_restricted_ipv6_ranges() {
+    for (addr, kind) in [
+        ("::1", "loopback"),
+        ("::", "unspecified"),
+        ("fd12:3456::1", "private"),
+        ("fd00:ec2::254", "cloud metadata"),
+        ("fe80::1", "link-local"),
+        ("fec0::1", "site-local"),
+        ("feff:ffff::1", "site-local"),
+        ("ff02::1", "multicast"),
+        // Embedded IPv4 addresses are judged by the IPv4 rules
+        ("::ffff:127.0.0.1", "loopback"),
+        ("::ffff:169.254.169.254", "cloud metadata"),
+        ("::127.0.0.1", "loopback"),
+        ("::169.254.169.254", "cloud metadata"),
+        ("::10.0.0.1", "private"),
+        ("64:ff9b::a00:1", "private"),
+        ("2002:c0a8:0101::1", "private"),
+    ] {
+        assert_eq!(restriction(ip(addr)), Some(kind), "{}", addr);
+    }
+}
+
+#[test]
+fn test_public_addresses_are_allowed() {
+    for addr in ["8.8.8.8", "172.32.0.1", "100.128.0.1", "2606:4700::1111", "::ffff:1.1.1.1", "2002:0808:0808::1"] {
+        assert_eq!(restriction(ip(addr)), None, "{}", addr);
+    }
+}
+
+#[test]
+fn test_check_url_rejects_schemes_and_restricted_literals() {
+    let policy = SsrfPolicy::new();
+
+    assert_eq!(
+        policy.check_url(&url("file:///etc/passwd")),
+        Err(SsrfError::Scheme("file".to_string()))
+    );
+    assert!(matches!(policy.check_url(&url("ftp://example.com/")), Err(SsrfError::Scheme(_))));
+    assert_eq!(
+        policy.check_url(&url("http://169.254.169.254/latest/meta-data/")),
+        Err(SsrfError::Restricted {
+            host: "169.254.169.254".to_string(),
+            ip: ip("169.254.169.254"),
+            kind: "cloud metadata",
+        })
+    );
+    assert!(policy.check_url(&url("http://[::1]:8080/admin")).is_err());
+    assert!(policy.check_url(&url("http://localhost:8080/admin")).is_err());
+    assert!(policy.check_url(&url("http://api.localhost/")).is_err());
+    // Decimal and hex IPv4 forms are normalized by the URL parser
+    assert!(policy.check_url(&url("http://2130706433/")).is_err());
+    assert!(policy.check_url(&url("http://0x7f.1/")).is_err());
+    assert!(policy.check_url(&url("https://example.com/")).is_ok());
+}
+
+#[test]
+fn test_restricted_error_message() {
+    let err = SsrfPolicy::new().check_url(&url("http://10.0.0.5/")).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Blocked request to 10.0.0.5: 10.0.0.5 is a private address. Add the host to the fetch allowlist to reach intranet services"
+    );
+}
+
+#[test]
+fn test_allowlist_entries() {
+    let policy = SsrfPolicy::with_allowlist(&[
+        "wiki.corp.example",
+        "*.intranet.example",
+        "10.20.0.0/16",
+        "fd00::5",
+        " ",
+    ])
+    .unwrap();
+
+    assert!(policy.allows_host("WIKI.corp.example."));
+    assert!(policy.allows_host("docs.intranet.example"));
+    assert!(!policy.allows_host("intranet.example"));
+    assert!(!policy.allows_host("evilintranet.example"));
+
+    assert!(policy.check_ip("wiki.corp.example", ip("10.1.1.1")).is_ok());
+    assert!(policy.check_ip("db.internal", ip("10.20.3.4")).is_ok());
+    assert!(policy.check_ip("db.internal", ip("10.21.3.4")).is_err());
+    assert!(policy.check_ip("v6.internal", ip("fd00::5")).is_ok());
+    assert!(policy.check_url(&url("http://10.20.0.1/")).is_ok());
+    assert!(policy.check_url(&url("http://127.0.0.1/")).is_err());
+}
+
+#[test]
+fn test_allowlist_rejects_invalid_entries() {
+    let err = SsrfPolicy::with_allowlist(&["http://wiki.corp.example/"]).unwrap_err();
+    assert!(err.to_string().starts_with("Invalid fetch allowlist entry 'http://wiki.corp.example/'"));
+}
+
+#[tokio::test]
+async fn test_resolve_checks_every_answer() {
+    let err = SsrfPolicy::new().resolve("localhost").await.unwrap_err();
+    assert!(matches!(err, SsrfError::Restricted { kind: "loopback", .. }));
+
+    let allowed = SsrfPolicy::with_allowlist(&["localhost"]).unwrap();
+    assert!(!allowed.resolve("localhost").await.unwrap().is_empty());
+}
+
+#[test]
+fn test_blocked_reason_looks_through_sources() {
+    #[derive(Debug)]
+    struct Wrapper(SsrfError);
+    impl std::fmt::Display for Wrapper {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("error sending request")
+        }
+    }
+    impl std::error::Error for Wrapper {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    let wrapped = Wrapper(SsrfError::Scheme("gopher".to_string()));
+    assert_eq!(blocked_reason(&wrapped), Some(&SsrfError::Scheme("gopher".to_string())));
+    assert_eq!(blocked_reason(&std::fmt::Error), None);
+}
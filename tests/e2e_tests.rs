@@ -12,20 +12,8 @@ struct TestServer {
 impl TestServer {
     /// Spawn the server process
     fn spawn() -> Result<Self, Box<dyn std::error::Error>> {
-        // Build the project first
-        let status = Command::new("cargo")
-            .args(["build", "--release"])
-            .current_dir("..")
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .status()?;
-
-        if !status.success() {
-            return Err("Failed to build server".into());
-        }
-
-        // Find the binary path
-        let binary_path = "../target/release/mcp-websearch";
+        // Cargo builds the binary before running integration tests
+        let binary_path = env!("CARGO_BIN_EXE_mcp-websearch");
 
         // Spawn the server
         let child = Command::new(binary_path)
@@ -81,7 +69,7 @@ fn test_e2e_initialize() {
     assert_eq!(response["id"], 1);
     assert!(response["result"].is_object());
     assert_eq!(response["result"]["serverInfo"]["name"], "mcp-websearch");
-    assert!(response["error"].is_null() || response["error"].is_absent());
+    assert!(response["error"].is_null());
 }
 
 #[test]
@@ -102,8 +90,11 @@ fn test_e2e_tools_list() {
     assert_eq!(response["jsonrpc"], "2.0");
     assert_eq!(response["id"], 1);
     assert!(response["result"]["tools"].is_array());
-    assert_eq!(response["result"]["tools"].as_array().unwrap().len(), 1);
+    assert_eq!(response["result"]["tools"].as_array().unwrap().len(), 4);
     assert_eq!(response["result"]["tools"][0]["name"], "web_search");
+    assert_eq!(response["result"]["tools"][1]["name"], "fetch_content");
+    assert_eq!(response["result"]["tools"][2]["name"], "extract_links");
+    assert_eq!(response["result"]["tools"][3]["name"], "fetch_selector");
 }
 
 #[test]
@@ -138,14 +129,9 @@ fn test_e2e_web_search_call() {
         assert!(!content.is_empty());
         assert_eq!(content[0]["type"], "text");
 
-        // Parse the text content as JSON
-        let search_response: Value = serde_json::from_str(
-            content[0]["text"].as_str().unwrap_or("{}")
-        ).unwrap_or_default();
-
-        assert_eq!(search_response["query"], "Rust programming");
-        assert!(search_response["results"].is_array());
-        assert!(search_response["totalResults"].is_number());
+        // Results are formatted as natural language for the LLM
+        let text = content[0]["text"].as_str().unwrap_or("");
+        assert!(text.contains("Rust programming"));
     } else if let Some(error) = response["error"].as_object() {
         // Network errors are acceptable in E2E tests
         eprintln!("Search failed (network error): {}", error["message"]);
@@ -243,17 +229,10 @@ fn test_e2e_large_result_set() {
 
     assert_eq!(response["jsonrpc"], "2.0");
 
-    // Parse the response
+    // Should return formatted results (or a network error)
     if let Some(result) = response["result"].as_object() {
-        let search_response: Value = serde_json::from_str(
-            result["content"][0]["text"].as_str().unwrap_or("{}")
-        ).unwrap_or_default();
-
-        // Should return results (or network error)
-        if !search_response["results"].is_null() {
-            let returned = search_response["returned"].as_u64().unwrap_or(0);
-            assert!(returned > 0, "Should return at least some results");
-        }
+        let text = result["content"][0]["text"].as_str().unwrap_or("");
+        assert!(text.contains("search results for") || text.contains("No results found"));
     }
 }
 
@@ -303,3 +282,97 @@ fn test_e2e_pagination() {
     assert_eq!(response1["result"]["isError"], serde_json::Value::Null);
     assert_eq!(response2["result"]["isError"], serde_json::Value::Null);
 }
+
+/// Run a one-shot CLI command and capture its output
+fn run_cli(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_mcp-websearch"))
+        .args(args)
+        .env_remove("MCP_WEBSEARCH_CONFIG")
+        .output()
+        .expect("Failed to run CLI")
+}
+
+#[test]
+fn test_e2e_cli_tools() {
+    let output = run_cli(&["tools"]);
+
+    assert!(output.status.success());
+    let tools: Value = serde_json::from_slice(&output.stdout).expect("tools prints JSON");
+    let names: Vec<&str> = tools["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|tool| tool["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["web_search", "fetch_content", "extract_links", "fetch_selector"]);
+}
+
+#[test]
+fn test_e2e_cli_fetch_reports_errors() {
+    let output = run_cli(&["fetch", "not a url"]);
+
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr).trim(),
+        "Error: Invalid URL 'not a url': relative URL without a base"
+    );
+}
+
+#[test]
+fn test_e2e_cli_fetch_validates_options() {
+    let output = run_cli(&["fetch", "https://example.com", "--format", "pdf"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("[possible values: markdown, text, html]"));
+
+    let output = run_cli(&["fetch", "https://example.com", "--length-unit", "words"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("[possible values: chars, tokens]"));
+}
+
+#[test]
+fn test_e2e_cli_fetch_length_in_tokens() {
+    // One-shot local page so the fetch never leaves the machine
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/page", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = [0u8; 4096];
+        let _ = std::io::Read::read(&mut stream, &mut request);
+        let body = "z".repeat(500);
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).unwrap();
+    });
+
+    let output = Command::new(env!("CARGO_BIN_EXE_mcp-websearch"))
+        .args(["fetch", &url, "--max-length", "10", "--length-unit", "tokens"])
+        .env_remove("MCP_WEBSEARCH_CONFIG")
+        .env("MCP_WEBSEARCH_FETCH_ALLOWLIST", "127.0.0.1")
+        .output()
+        .expect("Failed to run CLI");
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let text = String::from_utf8_lossy(&output.stdout);
+    // 10 tokens of about 4 characters each
+    assert!(text.contains(&"z".repeat(40)));
+    assert!(!text.contains(&"z".repeat(41)));
+}
+
+#[test]
+fn test_e2e_cli_rejects_invalid_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, "[search]\nrequests_per_minute = 0\n").unwrap();
+
+    let output = run_cli(&["--config", path.to_str().unwrap(), "tools"]);
+
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr).trim(),
+        format!("Error: Invalid configuration in {}: search.requests_per_minute must be at least 1", path.display())
+    );
+}